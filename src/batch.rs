@@ -0,0 +1,80 @@
+use layout::GlyphQuad;
+
+/// How the fragments of a triangle are covered.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VertexKind{
+    /// Textured by the atlas at `uv`, as glyph quads.
+    Atlas = 0,
+    /// Fully covered.
+    Solid = 1,
+    /// Loop-Blinn curve triangle, covered where `u * u - v < 0`.
+    CurveInside = 2,
+    /// Loop-Blinn curve triangle, covered where `u * u - v > 0`.
+    CurveOutside = 3,
+    /// Textured by the atlas at `uv` with the glyph's own premultiplied
+    /// colors, which aren't tinted.
+    ColoredAtlas = 4,
+}
+
+/// Vertex of a `VertexBatch`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Vertex{
+    /// Position with y growing upwards, in pixels for laid out text.
+    pub position: [f32; 2],
+    /// Atlas texture coordinates or curve coordinates depending on `kind`.
+    pub uv: [f32; 2],
+    pub kind: VertexKind,
+}
+
+/// Indexed triangles mixing glyph quads textured by the atlas and glyph
+/// meshes, so a single renderer can draw both.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VertexBatch{
+    pub vertices: Vec<Vertex>,
+    /// Counter clockwise triangles as triples of indices into `vertices`.
+    pub indices: Vec<u32>,
+}
+
+impl VertexBatch{
+    pub fn new() -> VertexBatch{
+        VertexBatch::default()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool{
+        self.indices.is_empty()
+    }
+
+    pub fn clear(&mut self){
+        self.vertices.clear();
+        self.indices.clear();
+    }
+
+    /// Appends a vertex, returning its index.
+    pub fn push_vertex(&mut self, position: [f32; 2], uv: [f32; 2], kind: VertexKind) -> u32{
+        self.vertices.push(Vertex{ position, uv, kind });
+        self.vertices.len() as u32 - 1
+    }
+
+    /// Appends the two triangles of a glyph quad.
+    pub fn push_quad(&mut self, quad: &GlyphQuad){
+        let kind = if quad.colored { VertexKind::ColoredAtlas } else { VertexKind::Atlas };
+        let top_left = self.push_vertex([quad.x0, quad.y0], [quad.s0, quad.t0], kind);
+        let top_right = self.push_vertex([quad.x1, quad.y0], [quad.s1, quad.t0], kind);
+        let bottom_right = self.push_vertex([quad.x1, quad.y1], [quad.s1, quad.t1], kind);
+        let bottom_left = self.push_vertex([quad.x0, quad.y1], [quad.s0, quad.t1], kind);
+        self.indices.extend_from_slice(&[top_left, bottom_left, bottom_right, top_left, bottom_right, top_right]);
+    }
+
+    /// Appends the triangles of `other` translated by (`x`, `y`).
+    pub fn append(&mut self, other: &VertexBatch, x: f32, y: f32){
+        let base = self.vertices.len() as u32;
+        self.vertices.extend(other.vertices.iter().map(|v| Vertex{
+            position: [v.position[0] + x, v.position[1] + y],
+            ..*v
+        }));
+        self.indices.extend(other.indices.iter().map(|i| i + base));
+    }
+}
@@ -0,0 +1,485 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::os::raw::c_void;
+use std::ptr;
+
+#[cfg(feature = "color")]
+use color::Palette;
+use embedding::EmbeddingPolicy;
+use ffi;
+use raster::{self, FixedSize, RasterOptions, Strike};
+use sdf::{FieldSource, SdfOptions};
+use stroke::StrokeOptions;
+use variation;
+use {Error, Hinting, LcdFilter, PixelFormat, RenderMode, TextureFont};
+
+/// Weights of freetype's default LCD filter, for freetype-gl to filter with.
+const FREETYPE_LCD_WEIGHTS: [u8; 5] = [0x08, 0x4D, 0x56, 0x4D, 0x08];
+
+enum Source{
+    File(String),
+    Memory(Vec<u8>),
+}
+
+/// Creates a `TextureFont` with options that can't be changed once glyphs
+/// start being loaded into the atlas.
+pub struct TextureFontBuilder{
+    source: Source,
+    pt_size: f32,
+    lcd_filter: Option<LcdFilter>,
+    rendermode: RenderMode,
+    outline_thickness: f32,
+    face_index: usize,
+    #[cfg(feature = "color")]
+    palette: Palette,
+    variations: Vec<([u8; 4], f32)>,
+    embedding_policy: Option<EmbeddingPolicy>,
+    fixed_size: Option<usize>,
+    raster: RasterOptions,
+}
+
+impl Source{
+    /// Opens the face `index` of the font in `library`.
+    unsafe fn open(&self, library: ffi::FT_Library, index: usize, face: &mut ffi::FT_Face) -> ffi::FT_Error{
+        match *self {
+            Source::File(ref path) => match CString::new(path.as_bytes()) {
+                Ok(path) => ffi::FT_New_Face(library, path.as_ptr(), index as ffi::FT_Long, face),
+                // paths with a NUL byte can't be passed to freetype
+                Err(_) => ffi::FT_Err_Cannot_Open_Resource as ffi::FT_Error,
+            },
+            Source::Memory(ref font_data) => {
+                ffi::FT_New_Memory_Face(
+                    library,
+                    font_data.as_ptr(),
+                    font_data.len() as ffi::FT_Long,
+                    index as ffi::FT_Long,
+                    face)
+            }
+        }
+    }
+
+    /// Checks that the face `index` can be opened, on its own, and finds the
+    /// bitmap strike it has to be loaded from: the strike `fixed_size` if
+    /// set, else the nearest one to `pixel_size` if it has no outlines.
+    unsafe fn probe(&self, index: usize, pixel_size: f32, fixed_size: Option<usize>) -> Result<Option<Strike>, Error>{
+        self.with_face(index, |face| match fixed_size {
+            Some(i) => match raster::fixed_sizes(face).get(i) {
+                Some(fixed) => Ok(raster::strike(face, i, fixed.size)),
+                None => Err(Error::InvalidFixedSize(i)),
+            },
+            None => Ok(raster::nearest_strike(face, pixel_size)),
+        })?
+    }
+
+    /// Runs `f` on the face `index` opened on its own.
+    unsafe fn with_face<T, F>(&self, index: usize, f: F) -> Result<T, Error>
+        where F: FnOnce(ffi::FT_Face) -> T
+    {
+        let mut library = ptr::null_mut();
+        if ffi::FT_Init_FreeType(&mut library) != 0 {
+            return Err(Error::FontCreation)
+        }
+        let mut face = ptr::null_mut();
+        let result = if self.open(library, index, &mut face) == 0 {
+            let result = f(face);
+            ffi::FT_Done_Face(face);
+            Ok(result)
+        }else if index > 0 && self.open(library, 0, &mut face) == 0 {
+            let num_faces = (*face).num_faces as usize;
+            ffi::FT_Done_Face(face);
+            Err(if index >= num_faces { Error::InvalidFaceIndex(index) } else { Error::FontCreation })
+        }else{
+            Err(Error::FontCreation)
+        };
+        ffi::FT_Done_FreeType(library);
+        result
+    }
+}
+
+/// Replaces the face freetype-gl opened, always the first one of the font,
+/// by the face `index` set up the same way.
+unsafe fn select_face(tex_font: *mut ffi::texture_font_t, source: &Source, index: usize, size: f32) -> bool{
+    let mut face = ptr::null_mut();
+    if source.open((*tex_font).library, index, &mut face) != 0 {
+        return false
+    }
+    if ffi::FT_Select_Charmap(face, ffi::FT_Encoding__FT_ENCODING_UNICODE) != 0
+        || ffi::FT_Set_Char_Size(face, (size * 64.) as ffi::FT_F26Dot6, 0, 72, 72) != 0
+    {
+        ffi::FT_Done_Face(face);
+        return false
+    }
+    ffi::FT_Done_Face((*tex_font).face);
+    let font = &mut *tex_font;
+    font.face = face;
+    update_line_metrics(font);
+    font.underline_position = ((*face).underline_position as f32 / 4096. * size).round().min(-2.);
+    font.underline_thickness = ((*face).underline_thickness as f32 / 4096. * size).round().max(1.);
+    true
+}
+
+/// Sets the ascender, descender, height and line gap of `font` from the size
+/// metrics of its face, the same way freetype-gl does.
+pub(crate) unsafe fn update_line_metrics(font: &mut ffi::texture_font_t){
+    let metrics = (*(*font.face).size).metrics;
+    font.ascender = (metrics.ascender >> 6) as f32;
+    font.descender = (metrics.descender >> 6) as f32;
+    font.height = (metrics.height >> 6) as f32;
+    font.linegap = font.height - font.ascender + font.descender;
+}
+
+/// Rewrites the texels freetype-gl reserves for line drawing and backgrounds
+/// in `R32F` atlases with 1. It fills them with 0xFF bytes, which are NaN as
+/// floats.
+unsafe fn fill_line_texels(tex_font: *mut ffi::texture_font_t){
+    let glyph = ffi::texture_font_get_glyph(tex_font, ptr::null());
+    if glyph.is_null() {
+        return
+    }
+    let atlas = &*(*tex_font).atlas;
+    let width = atlas.width as usize;
+    let texel = 1f32.to_ne_bytes();
+    // the glyph samples the center of the 4x4 region
+    let x = ((*glyph).s0 * width as f32).round() as usize - 2;
+    let y = ((*glyph).t0 * atlas.height as f32).round() as usize - 2;
+    for row in y .. y + 4 {
+        for col in x .. x + 4 {
+            let i = (row * width + col) * 4;
+            ptr::copy_nonoverlapping(texel.as_ptr(), atlas.data.add(i), 4);
+        }
+    }
+}
+
+impl TextureFontBuilder{
+    pub fn from_file(path: &str, pt_size: f32, format: PixelFormat) -> TextureFontBuilder{
+        TextureFontBuilder::new(Source::File(path.to_owned()), pt_size, format)
+    }
+
+    pub fn from_memory(font_data: Vec<u8>, pt_size: f32, format: PixelFormat) -> TextureFontBuilder{
+        TextureFontBuilder::new(Source::Memory(font_data), pt_size, format)
+    }
+
+    fn new(source: Source, pt_size: f32, format: PixelFormat) -> TextureFontBuilder{
+        TextureFontBuilder{
+            source,
+            pt_size,
+            lcd_filter: None,
+            rendermode: RenderMode::Normal,
+            outline_thickness: 0.,
+            face_index: 0,
+            #[cfg(feature = "color")]
+            palette: Palette::default(),
+            variations: vec![],
+            embedding_policy: None,
+            fixed_size: None,
+            raster: RasterOptions{
+                format,
+                #[cfg(feature = "color")]
+                foreground: [0., 0., 0., 1.],
+                ..RasterOptions::default()
+            },
+        }
+    }
+
+    /// How glyphs are rendered, `RenderMode::Normal` by default.
+    pub fn rendermode(mut self, rendermode: RenderMode) -> TextureFontBuilder{
+        self.rendermode = rendermode;
+        self
+    }
+
+    /// Render signed distance fields generated from rust with `options`
+    /// instead of freetype-gl's. Requires an `R8` atlas, or `R32F` for float
+    /// distances.
+    pub fn signed_distance_field(self, options: SdfOptions) -> TextureFontBuilder{
+        self.distance_field(options, FieldSource::Coverage)
+    }
+
+    /// Render signed distance fields measured from the exact glyph outlines
+    /// with `options` instead of rasterized glyphs, which keeps thin strokes
+    /// precise. Requires an `R8` or `R32F` atlas, `options.upsample` is
+    /// ignored.
+    pub fn outline_signed_distance_field(self, options: SdfOptions) -> TextureFontBuilder{
+        self.distance_field(options, FieldSource::Outline)
+    }
+
+    /// Render multi-channel signed distance fields measured from the glyph
+    /// outlines with `options`, which keep corners sharp at any scale.
+    /// Requires an `RGB8` atlas, `options.upsample` is ignored.
+    pub fn msdf(self, options: SdfOptions) -> TextureFontBuilder{
+        self.distance_field(options, FieldSource::MultiChannel)
+    }
+
+    fn distance_field(mut self, options: SdfOptions, source: FieldSource) -> TextureFontBuilder{
+        self.rendermode = RenderMode::SignedDistanceField;
+        self.raster.sdf = Some(options);
+        self.raster.field_source = source;
+        self
+    }
+
+    /// Thickness of the outline in pixels for the outline render modes.
+    pub fn outline_thickness(mut self, thickness: f32) -> TextureFontBuilder{
+        self.outline_thickness = thickness;
+        self
+    }
+
+    /// Render the fill and the outline of glyphs together, the fill coverage
+    /// in the red channel and the outline coverage in the green one, so both
+    /// share the same atlas region and can be drawn in a single pass.
+    /// Switches to `RenderMode::OutlineEdge` unless another outline render
+    /// mode is set. Requires an `RGBA8` atlas, where alpha holds the
+    /// highest coverage of both.
+    pub fn fill_and_outline(mut self) -> TextureFontBuilder{
+        if !self.rendermode.is_outline() {
+            self.rendermode = RenderMode::OutlineEdge;
+        }
+        self.raster.fill_and_outline = true;
+        self
+    }
+
+    /// Face to load from a font collection (TTC or OTC), see
+    /// `faces_in_file`. The first one by default.
+    pub fn face_index(mut self, index: usize) -> TextureFontBuilder{
+        self.face_index = index;
+        self
+    }
+
+    /// Fixed size bitmap strikes of the face to load, as used by bitmap
+    /// fonts like PCF, BDF or FNT ones and some fonts with embedded bitmaps.
+    pub fn fixed_sizes(&self) -> Result<Vec<FixedSize>, Error>{
+        unsafe{ self.source.with_face(self.face_index, |face| raster::fixed_sizes(face)) }
+    }
+
+    /// Load glyphs from the fixed size strike `index`, see `fixed_sizes`,
+    /// as they are without scaling. The font's size becomes the strike's
+    /// instead of the one it's created with. Monochrome strikes are packed
+    /// as 0 or 255 coverage and are best sampled without filtering.
+    ///
+    /// Fonts without outlines otherwise load the strike nearest to their
+    /// size and scale its bitmaps.
+    pub fn fixed_size(mut self, index: usize) -> TextureFontBuilder{
+        self.fixed_size = Some(index);
+        self
+    }
+
+    /// CPAL palette the layers of color glyphs are drawn with, the first one
+    /// by default. Layered color glyphs are only rendered in `RGBA8` atlases
+    /// with `RenderMode::Normal`, and with the `color` feature, which needs
+    /// FreeType 2.10 or later.
+    #[cfg(feature = "color")]
+    pub fn palette(mut self, palette: Palette) -> TextureFontBuilder{
+        self.palette = palette;
+        self
+    }
+
+    /// Straight alpha RGBA color, from 0 to 1, the layers of color glyphs
+    /// meant to be in the text color are drawn with, opaque black by
+    /// default. The color is baked into the atlas when glyphs are
+    /// rasterized, so those layers can't be tinted when drawn, and changing
+    /// it needs another font. Glyphs only made of such layers aren't colored
+    /// and are tinted when drawn instead.
+    #[cfg(feature = "color")]
+    pub fn foreground_color(mut self, color: [f32; 4]) -> TextureFontBuilder{
+        self.raster.foreground = color;
+        self
+    }
+
+    /// Sets the design axis `tag` of a variable font, eg. `b"wght"`, to
+    /// `value` clamped to the axis range. Axes the font doesn't have are
+    /// ignored.
+    pub fn variation(mut self, tag: &[u8; 4], value: f32) -> TextureFontBuilder{
+        self.variations.push((*tag, value));
+        self
+    }
+
+    /// Check the embedding permissions of the font against `policy` when its
+    /// atlas is exported with `TextureFont::atlas_for_export`. Not checked by
+    /// default.
+    pub fn embedding_policy(mut self, policy: EmbeddingPolicy) -> TextureFontBuilder{
+        self.embedding_policy = Some(policy);
+        self
+    }
+
+    /// How glyph outlines are stroked for the outline render modes. Joins
+    /// and caps other than round are rendered from rust.
+    pub fn stroke(mut self, options: StrokeOptions) -> TextureFontBuilder{
+        self.raster.stroke = options;
+        self
+    }
+
+    /// Hinting mode used when rendering glyphs, `Hinting::AutoHint` by
+    /// default.
+    pub fn hinting(mut self, hinting: Hinting) -> TextureFontBuilder{
+        self.raster.hinting = hinting;
+        self
+    }
+
+    /// Render glyphs for LCD screens, with 3 subpixels per pixel, filtered
+    /// with `filter`. Requires an `RGB8` atlas, where fonts are always
+    /// rendered for LCD with `LcdFilter::Default` unless specified otherwise.
+    pub fn lcd_filter(mut self, filter: LcdFilter) -> TextureFontBuilder{
+        self.lcd_filter = Some(filter);
+        self
+    }
+
+    /// Synthetic bold: embolden glyph outlines by `strength`, in 26.6
+    /// fractional pixels, before rasterizing them. Glyph advances grow by the
+    /// same amount.
+    pub fn embolden(mut self, strength: i64) -> TextureFontBuilder{
+        self.raster.embolden = strength as ffi::FT_Pos;
+        self
+    }
+
+    /// Synthetic oblique: shear glyph outlines horizontally by `shear` times
+    /// their height before rasterizing them. 0.2 gives a slant of around 11
+    /// degrees.
+    pub fn oblique(mut self, shear: f32) -> TextureFontBuilder{
+        self.raster.oblique = shear;
+        self
+    }
+
+    /// Subpixel positioning: rasterize each glyph at `positions` evenly spaced
+    /// horizontal offsets within a pixel, eg. 4 for 0, 1/4, 1/2 and 3/4, so
+    /// text can be laid out at fractional pen positions through
+    /// `TextureFont::glyph_at`. 1 disables it, which is the default.
+    pub fn subpixel_positions(mut self, positions: u8) -> TextureFontBuilder{
+        self.raster.subpixel_phases = if positions > 1 { positions } else { 0 };
+        self
+    }
+
+    pub fn build(mut self) -> Result<TextureFont, Error>{
+        let format = self.raster.format;
+        let sdf = self.rendermode == RenderMode::SignedDistanceField;
+        let msdf = sdf && self.raster.field_source == FieldSource::MultiChannel;
+        let layered = self.raster.fill_and_outline && self.rendermode.is_outline();
+        match format {
+            PixelFormat::R8 | PixelFormat::RGB8 | PixelFormat::R32F if layered =>
+                return Err(Error::IncompatiblePixelFormat(format, "fill and outline need an RGBA8 atlas")),
+            PixelFormat::RGB8 | PixelFormat::RGBA8 if sdf && !msdf =>
+                return Err(Error::IncompatiblePixelFormat(format, "distance fields need a single channel atlas")),
+            PixelFormat::R8 | PixelFormat::RGBA8 | PixelFormat::R32F if msdf =>
+                return Err(Error::IncompatiblePixelFormat(format, "multi-channel distance fields need an RGB8 atlas")),
+            PixelFormat::R32F if !sdf =>
+                return Err(Error::IncompatiblePixelFormat(format, "float atlases only hold distance fields")),
+            _ => (),
+        }
+        if self.lcd_filter.is_some() && (format != PixelFormat::RGB8 || msdf) {
+            return Err(Error::IncompatiblePixelFormat(format, "LCD filtering needs an RGB8 atlas without distance fields"))
+        }
+        self.raster.lcd_filter = self.lcd_filter.unwrap_or_default();
+        self.raster.fill_and_outline = layered;
+
+        // freetype-gl can only create fonts without outlines at the exact
+        // size of one of their strikes, so they are created at the nearest
+        // one and their bitmaps and metrics scaled to the requested size
+        self.raster.strike = unsafe{ self.source.probe(self.face_index, self.pt_size, self.fixed_size)? };
+        if let Some(strike) = self.raster.strike {
+            if self.fixed_size.is_some() {
+                self.pt_size = strike.size;
+            }
+            if self.rendermode != RenderMode::Normal {
+                return Err(Error::UnsupportedRenderMode(self.rendermode, "bitmap fonts are only rendered as they are"))
+            }
+            if strike.color && format != PixelFormat::RGBA8 {
+                return Err(Error::IncompatiblePixelFormat(format, "color bitmap fonts need an RGBA8 atlas"))
+            }
+        }
+        let size = self.raster.strike.map_or(self.pt_size, |strike| strike.size);
+        if !sdf {
+            self.raster.sdf = None;
+            self.raster.field_source = FieldSource::Coverage;
+        }
+
+        unsafe{
+            let tex_atlas = ffi::texture_atlas_new(512, 512, format.depth() as ffi::size_t);
+            if tex_atlas.is_null() {
+                return Err(Error::AtlasCreation)
+            }
+
+            // println!("allocated tex atlas  {},{}x{}", (*tex_atlas).width, (*tex_atlas).height, (*tex_atlas).depth);
+            let tex_font = match self.source {
+                Source::File(ref path) => match CString::new(path.as_bytes()) {
+                    Ok(path) => ffi::texture_font_new_from_file(tex_atlas, size, path.as_ptr()),
+                    Err(_) => ptr::null_mut(),
+                },
+                Source::Memory(ref font_data) => {
+                    ffi::texture_font_new_from_memory(
+                        tex_atlas,
+                        size,
+                        font_data.as_ptr() as *const c_void,
+                        font_data.len() as ffi::size_t)
+                }
+            };
+            if tex_font.is_null() {
+                ffi::texture_atlas_delete(tex_atlas);
+                return Err(Error::FontCreation)
+            }
+            if self.face_index != 0 && !select_face(tex_font, &self.source, self.face_index, size) {
+                ffi::texture_font_delete(tex_font);
+                ffi::texture_atlas_delete(tex_atlas);
+                return Err(Error::FontCreation)
+            }
+
+            // freetype-gl only knows about hinting with its auto-hinter or no
+            // hinting at all, other modes are rendered from rust
+            (*tex_font).hinting = (self.raster.hinting != Hinting::None) as i32;
+            (*tex_font).rendermode = self.rendermode as ffi::rendermode_t;
+            (*tex_font).outline_thickness = self.outline_thickness;
+            #[cfg(feature = "color")]
+            {
+                self.raster.palette = self.palette.index((*tex_font).face);
+            }
+            let axes = variation::axes((*tex_font).face);
+            let mut coordinates = variation::coordinates((*tex_font).face, axes.len());
+            for &(ref tag, value) in &self.variations {
+                variation::set(&axes, &mut coordinates, tag, value);
+            }
+            variation::apply((*tex_font).face, &mut coordinates);
+            self.raster.variable = !axes.is_empty();
+            if self.raster.variable {
+                update_line_metrics(&mut *tex_font);
+            }
+            if let Some(strike) = self.raster.strike {
+                ffi::FT_Select_Size((*tex_font).face, strike.index);
+                let font = &mut *tex_font;
+                font.size = self.pt_size;
+                font.height *= strike.scale;
+                font.linegap *= strike.scale;
+                font.ascender *= strike.scale;
+                font.descender *= strike.scale;
+                font.underline_position = (font.underline_position * strike.scale).round().min(-2.);
+                font.underline_thickness = (font.underline_thickness * strike.scale).round().max(1.);
+            }
+            if format == PixelFormat::R32F {
+                fill_line_texels(tex_font);
+            }
+            match self.raster.lcd_filter {
+                LcdFilter::Light => (*tex_font).filtering = 0,
+                LcdFilter::FreeType => (*tex_font).lcd_weights = FREETYPE_LCD_WEIGHTS,
+                LcdFilter::Custom(weights) => (*tex_font).lcd_weights = weights,
+                _ => (),
+            }
+
+            // memory faces point into the font data, which moves along
+            let bytes = match self.source {
+                Source::File(_) => None,
+                Source::Memory(font_data) => Some(font_data),
+            };
+            let font = TextureFont{
+                font: tex_font,
+                bytes,
+                raster: self.raster,
+                rendermode: self.rendermode,
+                embedding_policy: self.embedding_policy,
+                glyphs: RefCell::new(HashMap::new()),
+                axes,
+                instances: vec![coordinates],
+                instance: 0,
+            };
+            font.load_latin1();
+            // println!("loaded {} glyphs", ffi::vector_size((*tex_font).glyphs));
+
+            Ok(font)
+        }
+    }
+}
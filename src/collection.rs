@@ -0,0 +1,70 @@
+use std::ffi::{CStr, CString};
+use std::ptr;
+
+use ffi;
+use Error;
+
+/// Face of a font file, as listed by `faces_in_file` and `faces_in_memory`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FaceEntry{
+    /// Index to pass to `TextureFontBuilder::face_index`.
+    pub index: usize,
+    pub family: String,
+    pub style: String,
+}
+
+/// Faces of the font collection (TTC or OTC) at `path`, or its only face for
+/// single font files.
+pub fn faces_in_file(path: &str) -> Result<Vec<FaceEntry>, Error>{
+    let path = CString::new(path.as_bytes()).map_err(|_| Error::FontCreation)?;
+    unsafe{
+        list_faces(|library, index, face| ffi::FT_New_Face(library, path.as_ptr(), index, face))
+    }
+}
+
+/// Faces of the font collection in `font_data`, or its only face for single
+/// fonts.
+pub fn faces_in_memory(font_data: &[u8]) -> Result<Vec<FaceEntry>, Error>{
+    unsafe{
+        list_faces(|library, index, face| {
+            ffi::FT_New_Memory_Face(library, font_data.as_ptr(), font_data.len() as ffi::FT_Long, index, face)
+        })
+    }
+}
+
+pub(crate) unsafe fn c_string(s: *const ffi::FT_String) -> String{
+    if s.is_null() {
+        String::new()
+    }else{
+        CStr::from_ptr(s).to_string_lossy().into_owned()
+    }
+}
+
+/// Opens every face `open` finds until the number of faces reported by the
+/// first one.
+unsafe fn list_faces<F>(open: F) -> Result<Vec<FaceEntry>, Error>
+    where F: Fn(ffi::FT_Library, ffi::FT_Long, *mut ffi::FT_Face) -> ffi::FT_Error
+{
+    let mut library = ptr::null_mut();
+    if ffi::FT_Init_FreeType(&mut library) != 0 {
+        return Err(Error::FontCreation)
+    }
+    let mut faces = vec![];
+    let mut num_faces = 1;
+    while faces.len() < num_faces {
+        let mut face = ptr::null_mut();
+        if open(library, faces.len() as ffi::FT_Long, &mut face) != 0 {
+            ffi::FT_Done_FreeType(library);
+            return Err(Error::FontCreation)
+        }
+        num_faces = (*face).num_faces as usize;
+        faces.push(FaceEntry{
+            index: faces.len(),
+            family: c_string((*face).family_name),
+            style: c_string((*face).style_name),
+        });
+        ffi::FT_Done_Face(face);
+    }
+    ffi::FT_Done_FreeType(library);
+    Ok(faces)
+}
@@ -0,0 +1,237 @@
+use std::mem;
+use std::ptr;
+use std::slice;
+
+use ffi;
+use raster::{self, Bitmap, GlyphKey, RasterOptions, DEFAULT_PADDING};
+use TextureGlyph;
+
+/// Palette entry of layers drawn in the text color.
+const FOREGROUND: u16 = 0xFFFF;
+
+/// CPAL palette the layers of color glyphs are drawn with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Palette{
+    /// Palette at an index, the first one if the font has fewer.
+    Index(u16),
+    /// First palette meant for light backgrounds, or the first one.
+    Light,
+    /// First palette meant for dark backgrounds, or the first one.
+    Dark,
+}
+
+impl Default for Palette{
+    fn default() -> Palette{
+        Palette::Index(0)
+    }
+}
+
+impl Palette{
+    /// Index of the palette in `face`.
+    pub(crate) unsafe fn index(self, face: ffi::FT_Face) -> u16{
+        let mut data = mem::zeroed::<ffi::FT_Palette_Data>();
+        if ffi::FT_Palette_Data_Get(face, &mut data) != 0 || data.num_palettes == 0 {
+            return 0
+        }
+        let flag = match self {
+            Palette::Index(index) => return if index < data.num_palettes { index } else { 0 },
+            Palette::Light => ffi::FT_PALETTE_FOR_LIGHT_BACKGROUND,
+            Palette::Dark => ffi::FT_PALETTE_FOR_DARK_BACKGROUND,
+        };
+        if data.palette_flags.is_null() {
+            return 0
+        }
+        let flags = slice::from_raw_parts(data.palette_flags, data.num_palettes as usize);
+        flags.iter().position(|&f| f as u32 & flag != 0).unwrap_or(0) as u16
+    }
+}
+
+/// Layers of the color glyph `glyph_id` from the bottom up, as glyph ids and
+/// palette entries. Empty for glyphs without color layers.
+pub(crate) unsafe fn layers(face: ffi::FT_Face, glyph_id: u32) -> Vec<(u32, u16)>{
+    let mut iterator = ffi::FT_LayerIterator{ num_layers: 0, layer: 0, p: ptr::null_mut() };
+    let mut layer_glyph = 0;
+    let mut color = 0;
+    let mut layers = vec![];
+    while ffi::FT_Get_Color_Glyph_Layer(face, glyph_id, &mut layer_glyph, &mut color, &mut iterator) != 0 {
+        layers.push((layer_glyph, color as u16));
+    }
+    layers
+}
+
+/// Colors of the palette selected in `face`.
+unsafe fn palette_colors<'a>(face: ffi::FT_Face, palette: u16) -> &'a [ffi::FT_Color]{
+    let mut data = mem::zeroed::<ffi::FT_Palette_Data>();
+    let mut colors = ptr::null_mut();
+    if ffi::FT_Palette_Data_Get(face, &mut data) != 0
+        || ffi::FT_Palette_Select(face, palette, &mut colors) != 0
+        || colors.is_null()
+    {
+        return &[]
+    }
+    slice::from_raw_parts(colors, data.num_palette_entries as usize)
+}
+
+/// Rendered coverage of one layer with its straight alpha color.
+struct Layer{
+    coverage: Vec<u8>,
+    width: usize,
+    height: usize,
+    left: i32,
+    top: i32,
+    color: [f32; 4],
+}
+
+impl Layer{
+    fn bitmap<'a>(&'a self) -> Bitmap<'a>{
+        Bitmap{
+            data: &self.coverage,
+            width: self.width,
+            height: self.height,
+            pitch: self.width,
+            left: self.left,
+            top: self.top,
+        }
+    }
+}
+
+/// Draws `layers` over each other into premultiplied RGBA.
+fn composite<'a>(layers: &[Layer], data: &'a mut Vec<u8>) -> Bitmap<'a>{
+    let bitmaps = layers.iter().map(|layer| layer.bitmap()).collect::<Vec<_>>();
+    let (left, top, width, height) = match raster::union_bounds(&bitmaps.iter().collect::<Vec<_>>()) {
+        Some(bounds) => bounds,
+        None => return Bitmap{ data: &[], width: 0, height: 0, pitch: 0, left: 0, top: 0 },
+    };
+
+    let mut pixels = vec![0f32; width * height * 4];
+    for layer in layers {
+        let x0 = (layer.left - left) as usize;
+        let y0 = (top - layer.top) as usize;
+        for row in 0 .. layer.height {
+            for (col, &c) in layer.coverage[row * layer.width .. (row + 1) * layer.width].iter().enumerate() {
+                let alpha = layer.color[3] * c as f32 / 255.;
+                let i = ((y0 + row) * width + x0 + col) * 4;
+                for channel in 0 .. 3 {
+                    pixels[i + channel] = layer.color[channel] * alpha + pixels[i + channel] * (1. - alpha);
+                }
+                pixels[i + 3] = alpha + pixels[i + 3] * (1. - alpha);
+            }
+        }
+    }
+    data.clear();
+    data.extend(pixels.into_iter().map(|v| (v * 255.).round().min(255.) as u8));
+    Bitmap{
+        data,
+        width,
+        height,
+        pitch: width * 4,
+        left,
+        top,
+    }
+}
+
+/// Renders the `layers` of the glyph `key` with the font's palette and packs
+/// them composited in the `RGBA8` atlas. Layers in the text color are drawn
+/// with the foreground color, fixed from then on, or white when the glyph is
+/// only made of them, so it isn't colored and can still be tinted. Returns
+/// None on failure.
+pub(crate) unsafe fn load_color_glyph(
+    font: *mut ffi::texture_font_t,
+    codepoint: u32,
+    key: GlyphKey,
+    layers: &[(u32, u16)],
+    flags: ffi::FT_Int32,
+    options: &RasterOptions) -> Option<TextureGlyph>
+{
+    let face = (*font).face;
+    let palette = palette_colors(face, options.palette);
+    let colored = layers.iter().any(|&(_, entry)| entry != FOREGROUND && (entry as usize) < palette.len());
+    let foreground = if colored { options.foreground } else { [1.; 4] };
+    let mut rendered = Vec::with_capacity(layers.len());
+    for &(glyph_id, entry) in layers {
+        let color = match palette.get(entry as usize) {
+            Some(c) if entry != FOREGROUND =>
+                [c.red as f32 / 255., c.green as f32 / 255., c.blue as f32 / 255., c.alpha as f32 / 255.],
+            _ => foreground,
+        };
+        let slot = raster::load_styled(face, glyph_id, flags, key.phase, 1, options)?;
+        if ffi::FT_Render_Glyph(slot, ffi::FT_Render_Mode__FT_RENDER_MODE_NORMAL) != 0 {
+            return None
+        }
+        let bitmap = Bitmap::from_slot(slot, 1);
+        let mut coverage = Vec::with_capacity(bitmap.width * bitmap.height);
+        for row in 0 .. bitmap.height {
+            coverage.extend_from_slice(&bitmap.data[row * bitmap.pitch .. row * bitmap.pitch + bitmap.width]);
+        }
+        rendered.push(Layer{
+            coverage,
+            width: bitmap.width,
+            height: bitmap.height,
+            left: bitmap.left,
+            top: bitmap.top,
+            color,
+        });
+    }
+
+    // The base glyph gives the advance
+    let slot = raster::load_styled(face, key.glyph_id, flags, key.phase, 1, options)?;
+    let mut pixels = vec![];
+    let bitmap = composite(&rendered, &mut pixels);
+    let glyph = raster::pack_glyph(font, codepoint, key.glyph_id, &bitmap, DEFAULT_PADDING);
+    if glyph.is_null() {
+        return None
+    }
+    (*glyph).advance_x = (*slot).linearHoriAdvance as f32 / 65536. + options.embolden as f32 / 64.;
+    (*glyph).advance_y = (*slot).advance.y as f32 / 64.;
+    Some(TextureGlyph{ glyph, colored })
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn layer(coverage: Vec<u8>, width: usize, left: i32, top: i32, color: [f32; 4]) -> Layer{
+        let height = coverage.len() / width;
+        Layer{ coverage, width, height, left, top, color }
+    }
+
+    #[test]
+    fn composite_premultiplies(){
+        let layers = [layer(vec![255, 128], 2, 0, 1, [1., 0., 0., 1.])];
+        let mut data = vec![];
+        let bitmap = composite(&layers, &mut data);
+        assert_eq!((bitmap.width, bitmap.height, bitmap.pitch), (2, 1, 8));
+        assert_eq!(bitmap.data, &[255, 0, 0, 255, 128, 0, 0, 128]);
+    }
+
+    #[test]
+    fn composite_draws_layers_over_each_other(){
+        let layers = [
+            layer(vec![255, 255], 2, 0, 1, [1., 0., 0., 1.]),
+            layer(vec![255], 1, 1, 1, [0., 0., 1., 0.5]),
+        ];
+        let mut data = vec![];
+        let bitmap = composite(&layers, &mut data);
+        assert_eq!(bitmap.data, &[255, 0, 0, 255, 128, 0, 128, 255]);
+    }
+
+    #[test]
+    fn composite_union_of_bounds(){
+        let layers = [
+            layer(vec![255], 1, 0, 2, [1.; 4]),
+            layer(vec![255], 1, 2, 1, [1.; 4]),
+        ];
+        let mut data = vec![];
+        let bitmap = composite(&layers, &mut data);
+        assert_eq!((bitmap.left, bitmap.top, bitmap.width, bitmap.height), (0, 2, 3, 2));
+        assert_eq!(&bitmap.data[..4], &[255; 4]);
+        assert_eq!(&bitmap.data[4 .. 20], &[0; 16]);
+        assert_eq!(&bitmap.data[20 ..], &[255; 4]);
+    }
+
+    #[test]
+    fn composite_empty(){
+        let mut data = vec![];
+        assert_eq!(composite(&[], &mut data).width, 0);
+    }
+}
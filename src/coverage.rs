@@ -0,0 +1,217 @@
+use std::ops::RangeInclusive;
+
+use ffi;
+
+/// Set of codepoints, stored as sorted ranges.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CharSet{
+    /// Sorted, disjoint and non adjacent inclusive ranges.
+    ranges: Vec<(u32, u32)>,
+}
+
+impl CharSet{
+    /// Codepoints mapped to a glyph in the Unicode charmap of `face`.
+    pub(crate) unsafe fn from_face(face: ffi::FT_Face) -> CharSet{
+        let mut set = CharSet::default();
+        let mut glyph_id = 0;
+        let mut codepoint = ffi::FT_Get_First_Char(face, &mut glyph_id);
+        while glyph_id != 0 {
+            set.push(codepoint as u32);
+            codepoint = ffi::FT_Get_Next_Char(face, codepoint, &mut glyph_id);
+        }
+        set
+    }
+
+    /// Adds `codepoint`, which has to be greater than all the others.
+    fn push(&mut self, codepoint: u32){
+        match self.ranges.last_mut() {
+            Some(&mut (_, ref mut end)) if *end + 1 == codepoint => *end = codepoint,
+            _ => self.ranges.push((codepoint, codepoint)),
+        }
+    }
+
+    pub fn contains(&self, c: char) -> bool{
+        let c = c as u32;
+        match self.ranges.binary_search_by_key(&c, |&(start, _)| start) {
+            Ok(_) => true,
+            Err(0) => false,
+            Err(i) => c <= self.ranges[i - 1].1,
+        }
+    }
+
+    /// Number of codepoints in the set.
+    pub fn len(&self) -> usize{
+        self.ranges.iter().map(|&(start, end)| (end - start) as usize + 1).sum()
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.ranges.is_empty()
+    }
+
+    /// Ranges of consecutive codepoints in the set, in increasing order.
+    pub fn ranges<'a>(&'a self) -> impl Iterator<Item = RangeInclusive<u32>> + 'a{
+        self.ranges.iter().map(|&(start, end)| start ..= end)
+    }
+
+    /// Characters of the set in increasing order. Codepoints that aren't
+    /// characters, like surrogates some fonts map, are skipped.
+    pub fn chars<'a>(&'a self) -> impl Iterator<Item = char> + 'a{
+        self.ranges().flatten().filter_map(std::char::from_u32)
+    }
+
+    /// Characters of `text` missing from the set, without duplicates.
+    /// Whitespace is ignored.
+    pub fn missing(&self, text: &str) -> Vec<char>{
+        let mut missing = vec![];
+        for c in text.chars() {
+            if !c.is_whitespace() && !self.contains(c) && !missing.contains(&c) {
+                missing.push(c);
+            }
+        }
+        missing
+    }
+
+    /// How well the set covers each language of `LANGUAGES`: the letters of
+    /// its exemplar set, lowercase and uppercase, it misses.
+    pub fn language_support(&self) -> Vec<LanguageSupport>{
+        LANGUAGES.iter().map(|language| {
+            LanguageSupport{
+                language,
+                missing: self.missing(&language.letters()),
+            }
+        }).collect()
+    }
+
+    /// Languages of `LANGUAGES` whose letters are all in the set.
+    pub fn supported_languages(&self) -> Vec<&'static Language>{
+        self.language_support().into_iter()
+            .filter(|support| support.is_supported())
+            .map(|support| support.language)
+            .collect()
+    }
+}
+
+/// Language with the letters it needs to be written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Language{
+    /// BCP 47 language tag, eg. "pt".
+    pub tag: &'static str,
+    /// English name.
+    pub name: &'static str,
+    /// Lowercase letters and marks of the main CLDR exemplar set.
+    pub exemplars: &'static str,
+}
+
+impl Language{
+    /// Exemplars followed by their uppercase forms, for the ones that have
+    /// a single character uppercase form.
+    pub fn letters(&self) -> String{
+        let mut letters = self.exemplars.to_owned();
+        for c in self.exemplars.chars() {
+            let mut upper = c.to_uppercase();
+            if let (Some(u), None) = (upper.next(), upper.next()) {
+                if u != c {
+                    letters.push(u);
+                }
+            }
+        }
+        letters
+    }
+}
+
+/// Coverage of a language by a font, see `CharSet::language_support`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LanguageSupport{
+    pub language: &'static Language,
+    /// Letters of the language the font doesn't have.
+    pub missing: Vec<char>,
+}
+
+impl LanguageSupport{
+    #[inline]
+    pub fn is_supported(&self) -> bool{
+        self.missing.is_empty()
+    }
+}
+
+/// Languages checked by `CharSet::language_support`, written with
+/// alphabets, abjads or abugidas small enough to check exhaustively.
+pub static LANGUAGES: &[Language] = &[
+    Language{ tag: "ar", name: "Arabic", exemplars: "ءآأؤإئابةتثجحخدذرزسشصضطظعغفقكلمنهوىي" },
+    Language{ tag: "bg", name: "Bulgarian", exemplars: "абвгдежзийклмнопрстуфхцчшщъьюя" },
+    Language{ tag: "ca", name: "Catalan", exemplars: "aàbcçdeéèfghiíïjklŀmnoóòpqrstuúüvwxyz" },
+    Language{ tag: "cs", name: "Czech", exemplars: "aábcčdďeéěfghiíjklmnňoópqrřsštťuúůvwxyýzž" },
+    Language{ tag: "da", name: "Danish", exemplars: "abcdefghijklmnopqrstuvwxyzæøå" },
+    Language{ tag: "de", name: "German", exemplars: "abcdefghijklmnopqrstuvwxyzäöüß" },
+    Language{ tag: "el", name: "Greek", exemplars: "αάβγδεέζηήθιίϊΐκλμνξοόπρσςτυύϋΰφχψωώ" },
+    Language{ tag: "en", name: "English", exemplars: "abcdefghijklmnopqrstuvwxyz" },
+    Language{ tag: "es", name: "Spanish", exemplars: "abcdefghijklmnopqrstuvwxyzáéíñóúü" },
+    Language{ tag: "et", name: "Estonian", exemplars: "abcdefghijklmnopqrsšzžtuvwõäöüxy" },
+    Language{ tag: "fa", name: "Persian", exemplars: "آاءأؤئبپتثجچحخدذرزژسشصضطظعغفقکگلمنوهی" },
+    Language{ tag: "fi", name: "Finnish", exemplars: "abcdefghijklmnopqrstuvwxyzåäöšž" },
+    Language{ tag: "fr", name: "French", exemplars: "abcdefghijklmnopqrstuvwxyzàâæçéèêëîïôœùûüÿ" },
+    Language{ tag: "ga", name: "Irish", exemplars: "aábcdeéfghiílmnoóprstuú" },
+    Language{ tag: "he", name: "Hebrew", exemplars: "אבגדהוזחטיכךלמםנןסעפףצץקרשת" },
+    Language{ tag: "hi", name: "Hindi", exemplars: "\u{0901}\u{0902}\u{0903}अआइईउऊऋएऐऑओऔकखगघङचछजझञटठडढणतथदधनपफबभमयरलवशषसह\u{093C}ऽ\u{093E}\u{093F}\u{0940}\u{0941}\u{0942}\u{0943}\u{0945}\u{0947}\u{0948}\u{0949}\u{094B}\u{094C}\u{094D}" },
+    Language{ tag: "hr", name: "Croatian", exemplars: "abcčćdđefghijklmnoprsštuvzž" },
+    Language{ tag: "hu", name: "Hungarian", exemplars: "aábcdeéfghiíjklmnoóöőpqrstuúüűvwxyz" },
+    Language{ tag: "is", name: "Icelandic", exemplars: "aábdðeéfghiíjklmnoóprstuúvxyýþæö" },
+    Language{ tag: "it", name: "Italian", exemplars: "abcdefghijklmnopqrstuvwxyzàèéìòù" },
+    Language{ tag: "lt", name: "Lithuanian", exemplars: "aąbcčdeęėfghiįyjklmnoprsštuųūvzž" },
+    Language{ tag: "lv", name: "Latvian", exemplars: "aābcčdeēfgģhiījkķlļmnņoprsštuūvzž" },
+    Language{ tag: "nb", name: "Norwegian Bokmål", exemplars: "abcdefghijklmnopqrstuvwxyzæøå" },
+    Language{ tag: "nl", name: "Dutch", exemplars: "abcdefghijklmnopqrstuvwxyzéëïóöü" },
+    Language{ tag: "pl", name: "Polish", exemplars: "aąbcćdeęfghijklłmnńoóprsśtuwyzźż" },
+    Language{ tag: "pt", name: "Portuguese", exemplars: "abcdefghijklmnopqrstuvwxyzáàâãçéêíóôõú" },
+    Language{ tag: "ro", name: "Romanian", exemplars: "aăâbcdefghiîjklmnopqrsștțuvwxyz" },
+    Language{ tag: "ru", name: "Russian", exemplars: "абвгдежзийклмнопрстуфхцчшщъыьэюяё" },
+    Language{ tag: "sk", name: "Slovak", exemplars: "aáäbcčdďeéfghiíjklĺľmnňoóôpqrŕsštťuúvwxyýzž" },
+    Language{ tag: "sl", name: "Slovenian", exemplars: "abcčdefghijklmnoprsštuvzž" },
+    Language{ tag: "sr", name: "Serbian", exemplars: "абвгдђежзијклљмнњопрстћуфхцчџш" },
+    Language{ tag: "sv", name: "Swedish", exemplars: "abcdefghijklmnopqrstuvwxyzåäö" },
+    Language{ tag: "th", name: "Thai", exemplars: "กขฃคฅฆงจฉชซฌญฎฏฐฑฒณดตถทธนบปผฝพฟภมยรฤลฦวศษสหฬอฮฯะ\u{0E31}าำ\u{0E34}\u{0E35}\u{0E36}\u{0E37}\u{0E38}\u{0E39}เแโใไๅ\u{0E47}\u{0E48}\u{0E49}\u{0E4A}\u{0E4B}\u{0E4C}" },
+    Language{ tag: "tr", name: "Turkish", exemplars: "abcçdefgğhıiİjklmnoöprsştuüvyz" },
+    Language{ tag: "uk", name: "Ukrainian", exemplars: "абвгґдеєжзиіїйклмнопрстуфхцчшщьюя" },
+    Language{ tag: "vi", name: "Vietnamese", exemplars: "aàảãáạăằẳẵắặâầẩẫấậbcdđeèẻẽéẹêềểễếệghiìỉĩíịklmnoòỏõóọôồổỗốộơờởỡớợpqrstuùủũúụưừửữứựvxyỳỷỹýỵ" },
+];
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn set(codepoints: &[u32]) -> CharSet{
+        let mut set = CharSet::default();
+        for &codepoint in codepoints {
+            set.push(codepoint);
+        }
+        set
+    }
+
+    #[test]
+    fn push_merges_consecutive_codepoints(){
+        let set = set(&[0x41, 0x42, 0x43, 0x61, 0x63, 0x64]);
+        assert_eq!(set.ranges, vec![(0x41, 0x43), (0x61, 0x61), (0x63, 0x64)]);
+        assert_eq!(set.len(), 6);
+        assert!(!set.is_empty());
+        assert!(CharSet::default().is_empty());
+    }
+
+    #[test]
+    fn contains(){
+        let set = set(&[0x41, 0x42, 0x43, 0x61, 0x63, 0x64]);
+        for c in "ABCacd".chars() {
+            assert!(set.contains(c), "{:?}", c);
+        }
+        for c in "@DZ`beé".chars() {
+            assert!(!set.contains(c), "{:?}", c);
+        }
+        assert!(!CharSet::default().contains('A'));
+    }
+
+    #[test]
+    fn chars_and_missing(){
+        let set = set(&[0x41, 0x42, 0x61, 0xD800]);
+        assert_eq!(set.chars().collect::<String>(), "ABa");
+        assert_eq!(set.missing("Ab ba cab"), vec!['b', 'c']);
+    }
+}
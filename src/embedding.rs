@@ -0,0 +1,177 @@
+use ffi;
+use {Error, TextureAtlas};
+
+/// Usage the license of a font allows when it's embedded in a document,
+/// from least to most restrictive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmbeddingLicense{
+    /// The font may be embedded and installed permanently.
+    Installable,
+    /// The font may be embedded in documents that can be edited.
+    Editable,
+    /// The font may be embedded in documents that are only viewed or
+    /// printed.
+    PreviewAndPrint,
+    /// The font must not be embedded without permission from its owner.
+    Restricted,
+}
+
+/// Embedding permissions of a font, from the `fsType` field of its OS/2
+/// table. Fonts without one are installable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EmbeddingPermissions{
+    pub license: EmbeddingLicense,
+    /// The font must be embedded whole, not subset to the glyphs used.
+    pub no_subsetting: bool,
+    /// Only the bitmaps of the font may be embedded, not its outlines.
+    pub bitmap_only: bool,
+}
+
+impl EmbeddingPermissions{
+    /// Permissions of the `fsType` `flags`. The least restrictive license
+    /// applies when several are set.
+    pub fn from_flags(flags: u16) -> EmbeddingPermissions{
+        let flags = flags as u32;
+        let license = if flags & ffi::FT_FSTYPE_EDITABLE_EMBEDDING != 0 {
+            EmbeddingLicense::Editable
+        }else if flags & ffi::FT_FSTYPE_PREVIEW_AND_PRINT_EMBEDDING != 0 {
+            EmbeddingLicense::PreviewAndPrint
+        }else if flags & ffi::FT_FSTYPE_RESTRICTED_LICENSE_EMBEDDING != 0 {
+            EmbeddingLicense::Restricted
+        }else{
+            EmbeddingLicense::Installable
+        };
+        EmbeddingPermissions{
+            license,
+            no_subsetting: flags & ffi::FT_FSTYPE_NO_SUBSETTING != 0,
+            bitmap_only: flags & ffi::FT_FSTYPE_BITMAP_EMBEDDING_ONLY != 0,
+        }
+    }
+
+    /// Restrictions of these permissions, in the order they are checked.
+    fn restrictions(&self) -> Vec<EmbeddingRestriction>{
+        let mut restrictions = vec![];
+        if self.license == EmbeddingLicense::Restricted {
+            restrictions.push(EmbeddingRestriction::RestrictedLicense);
+        }
+        if self.bitmap_only {
+            restrictions.push(EmbeddingRestriction::BitmapOnly);
+        }
+        restrictions
+    }
+}
+
+/// Embedding restriction of a font an export ran into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmbeddingRestriction{
+    /// The font has a restricted license.
+    RestrictedLicense,
+    /// Only the bitmaps of the font may be embedded.
+    BitmapOnly,
+}
+
+/// What exports do with fonts that have an embedding restriction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmbeddingAction{
+    /// Export as if the font had no restriction.
+    Allow,
+    /// Export, and report the restriction with the exported data.
+    Warn,
+    /// Fail with `Error::EmbeddingRestricted`.
+    Refuse,
+}
+
+/// Checks exports of the atlas of a font do against its embedding
+/// permissions. Fonts only enforce one when built with
+/// `TextureFontBuilder::embedding_policy`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EmbeddingPolicy{
+    /// `EmbeddingAction::Refuse` by default.
+    pub restricted_license: EmbeddingAction,
+    /// `EmbeddingAction::Refuse` by default.
+    pub bitmap_only: EmbeddingAction,
+}
+
+impl Default for EmbeddingPolicy{
+    fn default() -> EmbeddingPolicy{
+        EmbeddingPolicy{
+            restricted_license: EmbeddingAction::Refuse,
+            bitmap_only: EmbeddingAction::Refuse,
+        }
+    }
+}
+
+impl EmbeddingPolicy{
+    fn action(&self, restriction: EmbeddingRestriction) -> EmbeddingAction{
+        match restriction {
+            EmbeddingRestriction::RestrictedLicense => self.restricted_license,
+            EmbeddingRestriction::BitmapOnly => self.bitmap_only,
+        }
+    }
+
+    /// Restrictions of `permissions` to warn about, or the first one the
+    /// policy refuses.
+    pub fn check(&self, permissions: &EmbeddingPermissions) -> Result<Vec<EmbeddingRestriction>, Error>{
+        let mut warnings = vec![];
+        for restriction in permissions.restrictions() {
+            match self.action(restriction) {
+                EmbeddingAction::Allow => (),
+                EmbeddingAction::Warn => warnings.push(restriction),
+                EmbeddingAction::Refuse => return Err(Error::EmbeddingRestricted(restriction)),
+            }
+        }
+        Ok(warnings)
+    }
+}
+
+/// Atlas of a font cleared for export by its embedding policy.
+pub struct AtlasExport{
+    pub atlas: TextureAtlas,
+    /// Restrictions of the font the policy warns about, to be shown before
+    /// shipping the atlas.
+    pub warnings: Vec<EmbeddingRestriction>,
+}
+
+pub(crate) unsafe fn permissions(face: ffi::FT_Face) -> EmbeddingPermissions{
+    EmbeddingPermissions::from_flags(ffi::FT_Get_FSType_Flags(face))
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn license(flags: u16) -> EmbeddingLicense{
+        EmbeddingPermissions::from_flags(flags).license
+    }
+
+    #[test]
+    fn licenses(){
+        assert_eq!(license(0), EmbeddingLicense::Installable);
+        assert_eq!(license(0x2), EmbeddingLicense::Restricted);
+        assert_eq!(license(0x4), EmbeddingLicense::PreviewAndPrint);
+        assert_eq!(license(0x8), EmbeddingLicense::Editable);
+    }
+
+    #[test]
+    fn least_restrictive_license(){
+        assert_eq!(license(0x2 | 0x4), EmbeddingLicense::PreviewAndPrint);
+        assert_eq!(license(0x2 | 0x8), EmbeddingLicense::Editable);
+        assert_eq!(license(0x4 | 0x8), EmbeddingLicense::Editable);
+    }
+
+    #[test]
+    fn no_subsetting_and_bitmap_only(){
+        let permissions = EmbeddingPermissions::from_flags(0x100);
+        assert_eq!(permissions.license, EmbeddingLicense::Installable);
+        assert!(permissions.no_subsetting);
+        assert!(!permissions.bitmap_only);
+
+        let permissions = EmbeddingPermissions::from_flags(0x200 | 0x8);
+        assert_eq!(permissions.license, EmbeddingLicense::Editable);
+        assert!(!permissions.no_subsetting);
+        assert!(permissions.bitmap_only);
+
+        let permissions = EmbeddingPermissions::from_flags(0);
+        assert!(!permissions.no_subsetting && !permissions.bitmap_only);
+    }
+}
@@ -11301,6 +11301,124 @@ extern "C" {
     #[doc = "   name is provided, not looking up special entries for named instances."]
     pub fn FT_Get_Postscript_Name(face: FT_Face) -> *const ::std::os::raw::c_char;
 }
+#[doc = " @struct:"]
+#[doc = "   FT_SfntName"]
+#[doc = ""]
+#[doc = " @description:"]
+#[doc = "   A structure used to model an SFNT 'name' table entry."]
+#[doc = ""]
+#[doc = " @note:"]
+#[doc = "   The `string` field is not null-terminated and is usually UTF-16BE"]
+#[doc = "   encoded for the Windows and Unicode platforms."]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct FT_SfntName_ {
+    pub platform_id: FT_UShort,
+    pub encoding_id: FT_UShort,
+    pub language_id: FT_UShort,
+    pub name_id: FT_UShort,
+    pub string: *mut FT_Byte,
+    pub string_len: FT_UInt,
+}
+pub type FT_SfntName = FT_SfntName_;
+extern "C" {
+    #[doc = " @function:"]
+    #[doc = "   FT_Get_Sfnt_Name_Count"]
+    #[doc = ""]
+    #[doc = " @description:"]
+    #[doc = "   Retrieve the number of name strings in the SFNT 'name' table."]
+    pub fn FT_Get_Sfnt_Name_Count(face: FT_Face) -> FT_UInt;
+}
+extern "C" {
+    #[doc = " @function:"]
+    #[doc = "   FT_Get_Sfnt_Name"]
+    #[doc = ""]
+    #[doc = " @description:"]
+    #[doc = "   Retrieve a string of the SFNT 'name' table for a given index."]
+    pub fn FT_Get_Sfnt_Name(face: FT_Face, idx: FT_UInt, aname: *mut FT_SfntName) -> FT_Error;
+}
+#[doc = " @struct:"]
+#[doc = "   TT_OS2"]
+#[doc = ""]
+#[doc = " @description:"]
+#[doc = "   A structure to model a TrueType 'OS/2' table.  All fields comply to"]
+#[doc = "   the OpenType specification."]
+#[doc = ""]
+#[doc = "   Note that we now support old Mac fonts that do not include an 'OS/2'"]
+#[doc = "   table.  In this case, the `version` field is always set to 0xFFFF."]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct TT_OS2_ {
+    pub version: FT_UShort,
+    pub xAvgCharWidth: FT_Short,
+    pub usWeightClass: FT_UShort,
+    pub usWidthClass: FT_UShort,
+    pub fsType: FT_UShort,
+    pub ySubscriptXSize: FT_Short,
+    pub ySubscriptYSize: FT_Short,
+    pub ySubscriptXOffset: FT_Short,
+    pub ySubscriptYOffset: FT_Short,
+    pub ySuperscriptXSize: FT_Short,
+    pub ySuperscriptYSize: FT_Short,
+    pub ySuperscriptXOffset: FT_Short,
+    pub ySuperscriptYOffset: FT_Short,
+    pub yStrikeoutSize: FT_Short,
+    pub yStrikeoutPosition: FT_Short,
+    pub sFamilyClass: FT_Short,
+    pub panose: [FT_Byte; 10usize],
+    pub ulUnicodeRange1: FT_ULong,
+    pub ulUnicodeRange2: FT_ULong,
+    pub ulUnicodeRange3: FT_ULong,
+    pub ulUnicodeRange4: FT_ULong,
+    pub achVendID: [FT_Char; 4usize],
+    pub fsSelection: FT_UShort,
+    pub usFirstCharIndex: FT_UShort,
+    pub usLastCharIndex: FT_UShort,
+    pub sTypoAscender: FT_Short,
+    pub sTypoDescender: FT_Short,
+    pub sTypoLineGap: FT_Short,
+    pub usWinAscent: FT_UShort,
+    pub usWinDescent: FT_UShort,
+    pub ulCodePageRange1: FT_ULong,
+    pub ulCodePageRange2: FT_ULong,
+    pub sxHeight: FT_Short,
+    pub sCapHeight: FT_Short,
+    pub usDefaultChar: FT_UShort,
+    pub usBreakChar: FT_UShort,
+    pub usMaxContext: FT_UShort,
+    pub usLowerOpticalPointSize: FT_UShort,
+    pub usUpperOpticalPointSize: FT_UShort,
+}
+pub type TT_OS2 = TT_OS2_;
+pub const FT_Sfnt_Tag__FT_SFNT_HEAD: FT_Sfnt_Tag_ = 0;
+pub const FT_Sfnt_Tag__FT_SFNT_MAXP: FT_Sfnt_Tag_ = 1;
+pub const FT_Sfnt_Tag__FT_SFNT_OS2: FT_Sfnt_Tag_ = 2;
+pub const FT_Sfnt_Tag__FT_SFNT_HHEA: FT_Sfnt_Tag_ = 3;
+pub const FT_Sfnt_Tag__FT_SFNT_VHEA: FT_Sfnt_Tag_ = 4;
+pub const FT_Sfnt_Tag__FT_SFNT_POST: FT_Sfnt_Tag_ = 5;
+pub const FT_Sfnt_Tag__FT_SFNT_PCLT: FT_Sfnt_Tag_ = 6;
+#[doc = " @enum:"]
+#[doc = "   FT_Sfnt_Tag"]
+#[doc = ""]
+#[doc = " @description:"]
+#[doc = "   An enumeration to specify indices of SFNT tables loaded and parsed by"]
+#[doc = "   FreeType during initialization of an SFNT font.  Used in the"]
+#[doc = "   @FT_Get_Sfnt_Table API function."]
+pub type FT_Sfnt_Tag_ = u32;
+pub use self::FT_Sfnt_Tag_ as FT_Sfnt_Tag;
+extern "C" {
+    #[doc = " @function:"]
+    #[doc = "   FT_Get_Sfnt_Table"]
+    #[doc = ""]
+    #[doc = " @description:"]
+    #[doc = "   Return a pointer to a given SFNT table stored within a face."]
+    #[doc = ""]
+    #[doc = " @return:"]
+    #[doc = "   A type-less pointer to the table.  This will be `NULL` in case of"]
+    #[doc = "   error, or if the corresponding table was not found **OR** loaded"]
+    #[doc = "   from the file."]
+    pub fn FT_Get_Sfnt_Table(face: FT_Face, tag: FT_Sfnt_Tag) -> *mut ::std::os::raw::c_void;
+}
 extern "C" {
     #[doc = " @function:"]
     #[doc = "   FT_Select_Charmap"]
@@ -11853,6 +11971,66 @@ extern "C" {
         iterator: *mut FT_LayerIterator,
     ) -> FT_Bool;
 }
+#[doc = " @struct:"]
+#[doc = "   FT_Color"]
+#[doc = ""]
+#[doc = " @description:"]
+#[doc = "   This structure models a BGRA color value of a 'CPAL' palette entry."]
+#[doc = ""]
+#[doc = "   The used color space is sRGB; the colors are not pre-multiplied, and"]
+#[doc = "   alpha values must be explicitly set."]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct FT_Color_ {
+    pub blue: FT_Byte,
+    pub green: FT_Byte,
+    pub red: FT_Byte,
+    pub alpha: FT_Byte,
+}
+pub type FT_Color = FT_Color_;
+pub const FT_PALETTE_FOR_LIGHT_BACKGROUND: u32 = 1;
+pub const FT_PALETTE_FOR_DARK_BACKGROUND: u32 = 2;
+#[doc = " @struct:"]
+#[doc = "   FT_Palette_Data"]
+#[doc = ""]
+#[doc = " @description:"]
+#[doc = "   This structure holds the data of the 'CPAL' table."]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct FT_Palette_Data_ {
+    pub num_palettes: FT_UShort,
+    pub palette_name_ids: *const FT_UShort,
+    pub palette_flags: *const FT_UShort,
+    pub num_palette_entries: FT_UShort,
+    pub palette_entry_name_ids: *const FT_UShort,
+}
+pub type FT_Palette_Data = FT_Palette_Data_;
+extern "C" {
+    #[doc = " @function:"]
+    #[doc = "   FT_Palette_Data_Get"]
+    #[doc = ""]
+    #[doc = " @description:"]
+    #[doc = "   Retrieve the face's color palette data."]
+    pub fn FT_Palette_Data_Get(face: FT_Face, apalette: *mut FT_Palette_Data) -> FT_Error;
+}
+extern "C" {
+    #[doc = " @function:"]
+    #[doc = "   FT_Palette_Select"]
+    #[doc = ""]
+    #[doc = " @description:"]
+    #[doc = "   This function has two purposes."]
+    #[doc = ""]
+    #[doc = "   (1) It activates a palette for rendering color glyphs, and"]
+    #[doc = ""]
+    #[doc = "   (2) it retrieves all (unmodified) color entries of this palette.  This"]
+    #[doc = "       function returns a read-write array, which means that a calling"]
+    #[doc = "       application can modify the palette entries on demand."]
+    pub fn FT_Palette_Select(
+        face: FT_Face,
+        palette_index: FT_UShort,
+        apalette: *mut *mut FT_Color,
+    ) -> FT_Error;
+}
 extern "C" {
     #[doc = " @function:"]
     #[doc = "   FT_Get_FSType_Flags"]
@@ -12034,6 +12212,86 @@ extern "C" {
     #[doc = "   2.3.6"]
     pub fn FT_Face_GetCharsOfVariant(face: FT_Face, variantSelector: FT_ULong) -> *mut FT_UInt32;
 }
+#[doc = " @struct:"]
+#[doc = "   FT_Var_Axis"]
+#[doc = ""]
+#[doc = " @description:"]
+#[doc = "   A structure to model a given axis in design space for Multiple"]
+#[doc = "   Masters, TrueType GX, and OpenType variation fonts."]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct FT_Var_Axis_ {
+    pub name: *mut FT_String,
+    pub minimum: FT_Fixed,
+    pub def: FT_Fixed,
+    pub maximum: FT_Fixed,
+    pub tag: FT_ULong,
+    pub strid: FT_UInt,
+}
+pub type FT_Var_Axis = FT_Var_Axis_;
+#[doc = " @struct:"]
+#[doc = "   FT_Var_Named_Style"]
+#[doc = ""]
+#[doc = " @description:"]
+#[doc = "   A structure to model a named instance in a TrueType GX or OpenType"]
+#[doc = "   variation font."]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct FT_Var_Named_Style_ {
+    pub coords: *mut FT_Fixed,
+    pub strid: FT_UInt,
+    pub psid: FT_UInt,
+}
+pub type FT_Var_Named_Style = FT_Var_Named_Style_;
+#[doc = " @struct:"]
+#[doc = "   FT_MM_Var"]
+#[doc = ""]
+#[doc = " @description:"]
+#[doc = "   A structure to model the axes and space of an Adobe MM, TrueType GX,"]
+#[doc = "   or OpenType variation font."]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct FT_MM_Var_ {
+    pub num_axis: FT_UInt,
+    pub num_designs: FT_UInt,
+    pub num_namedstyles: FT_UInt,
+    pub axis: *mut FT_Var_Axis,
+    pub namedstyle: *mut FT_Var_Named_Style,
+}
+pub type FT_MM_Var = FT_MM_Var_;
+extern "C" {
+    #[doc = " @function:"]
+    #[doc = "   FT_Get_MM_Var"]
+    #[doc = ""]
+    #[doc = " @description:"]
+    #[doc = "   Retrieve a variation descriptor for a font."]
+    pub fn FT_Get_MM_Var(face: FT_Face, amaster: *mut *mut FT_MM_Var) -> FT_Error;
+}
+extern "C" {
+    #[doc = " @function:"]
+    #[doc = "   FT_Set_Var_Design_Coordinates"]
+    #[doc = ""]
+    #[doc = " @description:"]
+    #[doc = "   Choose an interpolated font design through design coordinates."]
+    pub fn FT_Set_Var_Design_Coordinates(
+        face: FT_Face,
+        num_coords: FT_UInt,
+        coords: *mut FT_Fixed,
+    ) -> FT_Error;
+}
+extern "C" {
+    #[doc = " @function:"]
+    #[doc = "   FT_Get_Var_Design_Coordinates"]
+    #[doc = ""]
+    #[doc = " @description:"]
+    #[doc = "   Get the design coordinates of the currently selected interpolated"]
+    #[doc = "   font."]
+    pub fn FT_Get_Var_Design_Coordinates(
+        face: FT_Face,
+        num_coords: FT_UInt,
+        coords: *mut FT_Fixed,
+    ) -> FT_Error;
+}
 extern "C" {
     #[doc = " @function:"]
     #[doc = "   FT_MulDiv"]
@@ -12181,6 +12439,291 @@ extern "C" {
     #[doc = "   The result is undefined if either `vector` or `matrix` is invalid."]
     pub fn FT_Vector_Transform(vector: *mut FT_Vector, matrix: *const FT_Matrix);
 }
+extern "C" {
+    #[doc = " @function:"]
+    #[doc = "   FT_Outline_Embolden"]
+    #[doc = ""]
+    #[doc = " @description:"]
+    #[doc = "   Embolden an outline.  The new outline will be at most 4~times"]
+    #[doc = "   `strength` pixels wider and higher.  You may think of the left and"]
+    #[doc = "   bottom borders as unchanged."]
+    #[doc = ""]
+    #[doc = "   Negative `strength` values to reduce the outline thickness are"]
+    #[doc = "   possible also."]
+    #[doc = ""]
+    #[doc = " @inout:"]
+    #[doc = "   outline ::"]
+    #[doc = "     A handle to the target outline."]
+    #[doc = ""]
+    #[doc = " @input:"]
+    #[doc = "   strength ::"]
+    #[doc = "     How strong the glyph is emboldened.  Expressed in 26.6 pixel format."]
+    #[doc = ""]
+    #[doc = " @return:"]
+    #[doc = "   FreeType error code.  0~means success."]
+    pub fn FT_Outline_Embolden(outline: *mut FT_Outline, strength: FT_Pos) -> FT_Error;
+}
+extern "C" {
+    #[doc = " @function:"]
+    #[doc = "   FT_Outline_EmboldenXY"]
+    #[doc = ""]
+    #[doc = " @description:"]
+    #[doc = "   Embolden an outline.  The new outline will be `xstrength` pixels"]
+    #[doc = "   wider and `ystrength` pixels higher.  Otherwise, it is similar to"]
+    #[doc = "   @FT_Outline_Embolden, which uses the same strength in both"]
+    #[doc = "   directions."]
+    pub fn FT_Outline_EmboldenXY(
+        outline: *mut FT_Outline,
+        xstrength: FT_Pos,
+        ystrength: FT_Pos,
+    ) -> FT_Error;
+}
+extern "C" {
+    #[doc = " @function:"]
+    #[doc = "   FT_Outline_Decompose"]
+    #[doc = ""]
+    #[doc = " @description:"]
+    #[doc = "   Walk over an outline's structure to decompose it into individual"]
+    #[doc = "   segments and Bezier arcs.  This function also emits 'move to'"]
+    #[doc = "   operations to indicate the start of new contours in the outline."]
+    #[doc = ""]
+    #[doc = " @input:"]
+    #[doc = "   outline ::"]
+    #[doc = "     A pointer to the source target."]
+    #[doc = ""]
+    #[doc = "   func_interface ::"]
+    #[doc = "     A table of 'emitters', i.e., function pointers called during"]
+    #[doc = "     decomposition to indicate path operations."]
+    #[doc = ""]
+    #[doc = " @inout:"]
+    #[doc = "   user ::"]
+    #[doc = "     A typeless pointer that is passed to each emitter during the"]
+    #[doc = "     decomposition.  It can be used to store the state during the"]
+    #[doc = "     decomposition."]
+    #[doc = ""]
+    #[doc = " @return:"]
+    #[doc = "   FreeType error code.  0~means success."]
+    pub fn FT_Outline_Decompose(
+        outline: *mut FT_Outline,
+        func_interface: *const FT_Outline_Funcs,
+        user: *mut ::std::os::raw::c_void,
+    ) -> FT_Error;
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct FT_Glyph_Class_ {
+    _unused: [u8; 0],
+}
+pub type FT_Glyph_Class = FT_Glyph_Class_;
+#[doc = " @type:"]
+#[doc = "   FT_Glyph"]
+#[doc = ""]
+#[doc = " @description:"]
+#[doc = "   Handle to an object used to model generic glyph images.  It is a"]
+#[doc = "   pointer to the @FT_GlyphRec structure and can contain a glyph bitmap"]
+#[doc = "   or pointer."]
+pub type FT_Glyph = *mut FT_GlyphRec_;
+#[doc = " @struct:"]
+#[doc = "   FT_GlyphRec"]
+#[doc = ""]
+#[doc = " @description:"]
+#[doc = "   The root glyph structure contains a given glyph image plus its"]
+#[doc = "   advance width in 16.16 fixed-point format."]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct FT_GlyphRec_ {
+    pub library: FT_Library,
+    pub clazz: *const FT_Glyph_Class,
+    pub format: FT_Glyph_Format,
+    pub advance: FT_Vector,
+}
+pub type FT_GlyphRec = FT_GlyphRec_;
+#[doc = " @type:"]
+#[doc = "   FT_BitmapGlyph"]
+#[doc = ""]
+#[doc = " @description:"]
+#[doc = "   A handle to an object used to model a bitmap glyph image.  This is a"]
+#[doc = "   sub-class of @FT_Glyph, and a pointer to @FT_BitmapGlyphRec."]
+pub type FT_BitmapGlyph = *mut FT_BitmapGlyphRec_;
+#[doc = " @struct:"]
+#[doc = "   FT_BitmapGlyphRec"]
+#[doc = ""]
+#[doc = " @description:"]
+#[doc = "   A structure used for bitmap glyph images.  This really is a"]
+#[doc = "   'sub-class' of @FT_GlyphRec."]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct FT_BitmapGlyphRec_ {
+    pub root: FT_GlyphRec,
+    pub left: FT_Int,
+    pub top: FT_Int,
+    pub bitmap: FT_Bitmap,
+}
+pub type FT_BitmapGlyphRec = FT_BitmapGlyphRec_;
+extern "C" {
+    #[doc = " @function:"]
+    #[doc = "   FT_Get_Glyph"]
+    #[doc = ""]
+    #[doc = " @description:"]
+    #[doc = "   A function used to extract a glyph image from a slot.  Note that"]
+    #[doc = "   the created @FT_Glyph object must be released with @FT_Done_Glyph."]
+    pub fn FT_Get_Glyph(slot: FT_GlyphSlot, aglyph: *mut FT_Glyph) -> FT_Error;
+}
+extern "C" {
+    #[doc = " @function:"]
+    #[doc = "   FT_Glyph_To_Bitmap"]
+    #[doc = ""]
+    #[doc = " @description:"]
+    #[doc = "   Convert a given glyph object to a bitmap glyph object."]
+    pub fn FT_Glyph_To_Bitmap(
+        the_glyph: *mut FT_Glyph,
+        render_mode: FT_Render_Mode,
+        origin: *const FT_Vector,
+        destroy: FT_Bool,
+    ) -> FT_Error;
+}
+extern "C" {
+    #[doc = " @function:"]
+    #[doc = "   FT_Done_Glyph"]
+    #[doc = ""]
+    #[doc = " @description:"]
+    #[doc = "   Destroy a given glyph."]
+    pub fn FT_Done_Glyph(glyph: FT_Glyph);
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct FT_StrokerRec_ {
+    _unused: [u8; 0],
+}
+#[doc = " @type:"]
+#[doc = "   FT_Stroker"]
+#[doc = ""]
+#[doc = " @description:"]
+#[doc = "   Opaque handle to a path stroker object."]
+pub type FT_Stroker = *mut FT_StrokerRec_;
+pub const FT_Stroker_LineJoin__FT_STROKER_LINEJOIN_ROUND: FT_Stroker_LineJoin_ = 0;
+pub const FT_Stroker_LineJoin__FT_STROKER_LINEJOIN_BEVEL: FT_Stroker_LineJoin_ = 1;
+pub const FT_Stroker_LineJoin__FT_STROKER_LINEJOIN_MITER_VARIABLE: FT_Stroker_LineJoin_ = 2;
+pub const FT_Stroker_LineJoin__FT_STROKER_LINEJOIN_MITER: FT_Stroker_LineJoin_ = 2;
+pub const FT_Stroker_LineJoin__FT_STROKER_LINEJOIN_MITER_FIXED: FT_Stroker_LineJoin_ = 3;
+#[doc = " @enum:"]
+#[doc = "   FT_Stroker_LineJoin"]
+#[doc = ""]
+#[doc = " @description:"]
+#[doc = "   These values determine how two joining lines are rendered in a"]
+#[doc = "   stroker."]
+pub type FT_Stroker_LineJoin_ = u32;
+pub use self::FT_Stroker_LineJoin_ as FT_Stroker_LineJoin;
+pub const FT_Stroker_LineCap__FT_STROKER_LINECAP_BUTT: FT_Stroker_LineCap_ = 0;
+pub const FT_Stroker_LineCap__FT_STROKER_LINECAP_ROUND: FT_Stroker_LineCap_ = 1;
+pub const FT_Stroker_LineCap__FT_STROKER_LINECAP_SQUARE: FT_Stroker_LineCap_ = 2;
+#[doc = " @enum:"]
+#[doc = "   FT_Stroker_LineCap"]
+#[doc = ""]
+#[doc = " @description:"]
+#[doc = "   These values determine how the end of opened sub-paths are rendered"]
+#[doc = "   in a stroke."]
+pub type FT_Stroker_LineCap_ = u32;
+pub use self::FT_Stroker_LineCap_ as FT_Stroker_LineCap;
+extern "C" {
+    #[doc = " @function:"]
+    #[doc = "   FT_Stroker_New"]
+    #[doc = ""]
+    #[doc = " @description:"]
+    #[doc = "   Create a new stroker object."]
+    pub fn FT_Stroker_New(library: FT_Library, astroker: *mut FT_Stroker) -> FT_Error;
+}
+extern "C" {
+    #[doc = " @function:"]
+    #[doc = "   FT_Stroker_Set"]
+    #[doc = ""]
+    #[doc = " @description:"]
+    #[doc = "   Reset a stroker object's attributes.  `radius` is in 26.6 pixels"]
+    #[doc = "   and `miter_limit` in 16.16 fixed-point format."]
+    pub fn FT_Stroker_Set(
+        stroker: FT_Stroker,
+        radius: FT_Fixed,
+        line_cap: FT_Stroker_LineCap,
+        line_join: FT_Stroker_LineJoin,
+        miter_limit: FT_Fixed,
+    );
+}
+extern "C" {
+    #[doc = " @function:"]
+    #[doc = "   FT_Stroker_Done"]
+    #[doc = ""]
+    #[doc = " @description:"]
+    #[doc = "   Destroy a stroker object."]
+    pub fn FT_Stroker_Done(stroker: FT_Stroker);
+}
+extern "C" {
+    #[doc = " @function:"]
+    #[doc = "   FT_Glyph_Stroke"]
+    #[doc = ""]
+    #[doc = " @description:"]
+    #[doc = "   Stroke a given outline glyph object with a given stroker."]
+    pub fn FT_Glyph_Stroke(pglyph: *mut FT_Glyph, stroker: FT_Stroker, destroy: FT_Bool) -> FT_Error;
+}
+extern "C" {
+    #[doc = " @function:"]
+    #[doc = "   FT_Glyph_StrokeBorder"]
+    #[doc = ""]
+    #[doc = " @description:"]
+    #[doc = "   Stroke a given outline glyph object with a given stroker, but only"]
+    #[doc = "   return either its inside or outside border."]
+    pub fn FT_Glyph_StrokeBorder(
+        pglyph: *mut FT_Glyph,
+        stroker: FT_Stroker,
+        inside: FT_Bool,
+        destroy: FT_Bool,
+    ) -> FT_Error;
+}
+pub const FT_LcdFilter__FT_LCD_FILTER_NONE: FT_LcdFilter_ = 0;
+pub const FT_LcdFilter__FT_LCD_FILTER_DEFAULT: FT_LcdFilter_ = 1;
+pub const FT_LcdFilter__FT_LCD_FILTER_LIGHT: FT_LcdFilter_ = 2;
+pub const FT_LcdFilter__FT_LCD_FILTER_LEGACY1: FT_LcdFilter_ = 3;
+pub const FT_LcdFilter__FT_LCD_FILTER_LEGACY: FT_LcdFilter_ = 16;
+pub const FT_LcdFilter__FT_LCD_FILTER_MAX: FT_LcdFilter_ = 17;
+#[doc = " @enum:"]
+#[doc = "   FT_LcdFilter"]
+#[doc = ""]
+#[doc = " @description:"]
+#[doc = "   A list of values to identify various types of LCD filters."]
+pub type FT_LcdFilter_ = u32;
+pub use self::FT_LcdFilter_ as FT_LcdFilter;
+extern "C" {
+    #[doc = " @function:"]
+    #[doc = "   FT_Library_SetLcdFilter"]
+    #[doc = ""]
+    #[doc = " @description:"]
+    #[doc = "   This function is used to apply color filtering to LCD decimated"]
+    #[doc = "   bitmaps, like the ones used when calling @FT_Render_Glyph with"]
+    #[doc = "   @FT_RENDER_MODE_LCD or @FT_RENDER_MODE_LCD_V."]
+    #[doc = ""]
+    #[doc = " @return:"]
+    #[doc = "   FreeType error code.  0~means success."]
+    pub fn FT_Library_SetLcdFilter(library: FT_Library, filter: FT_LcdFilter) -> FT_Error;
+}
+extern "C" {
+    #[doc = " @function:"]
+    #[doc = "   FT_Library_SetLcdFilterWeights"]
+    #[doc = ""]
+    #[doc = " @description:"]
+    #[doc = "   This function can be used to enable LCD filter with custom weights,"]
+    #[doc = "   instead of using presets in @FT_Library_SetLcdFilter."]
+    #[doc = ""]
+    #[doc = " @input:"]
+    #[doc = "   weights ::"]
+    #[doc = "     A pointer to an array; the function copies the first five bytes and"]
+    #[doc = "     uses them to specify the filter weights in 1/256th units."]
+    #[doc = ""]
+    #[doc = " @return:"]
+    #[doc = "   FreeType error code.  0~means success."]
+    pub fn FT_Library_SetLcdFilterWeights(
+        library: FT_Library,
+        weights: *mut ::std::os::raw::c_uchar,
+    ) -> FT_Error;
+}
 extern "C" {
     #[doc = " @function:"]
     #[doc = "   FT_Library_Version"]
@@ -14803,6 +15346,12 @@ extern "C" {
     #[doc = " @return a new empty glyph (not valid)"]
     pub fn texture_glyph_new() -> *mut texture_glyph_t;
 }
+extern "C" {
+    #[doc = " Delete a glyph"]
+    #[doc = ""]
+    #[doc = " @param self a valid texture glyph"]
+    pub fn texture_glyph_delete(self_: *mut texture_glyph_t);
+}
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct __locale_data {
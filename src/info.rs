@@ -0,0 +1,177 @@
+use std::mem;
+use std::slice;
+
+use collection::c_string;
+use ffi;
+
+const NAME_COPYRIGHT: u16 = 0;
+const NAME_FAMILY: u16 = 1;
+const NAME_SUBFAMILY: u16 = 2;
+const NAME_VERSION: u16 = 5;
+const NAME_DESIGNER: u16 = 9;
+const NAME_LICENSE: u16 = 13;
+const NAME_LICENSE_URL: u16 = 14;
+const NAME_TYPOGRAPHIC_FAMILY: u16 = 16;
+const NAME_TYPOGRAPHIC_SUBFAMILY: u16 = 17;
+
+const PLATFORM_UNICODE: u16 = 0;
+const PLATFORM_MACINTOSH: u16 = 1;
+const PLATFORM_WINDOWS: u16 = 3;
+/// Windows language id of US English.
+const LANGUAGE_EN_US: u16 = 0x409;
+
+/// Metadata of a font, mostly from its `name` table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FontInfo{
+    /// Typographic family name if the font has one, eg. "Source Sans Pro".
+    pub family: String,
+    /// Typographic style name if the font has one, eg. "Semibold Italic".
+    pub style: String,
+    pub postscript_name: Option<String>,
+    /// Version string as written in the font, eg. "Version 2.010".
+    pub version: Option<String>,
+    pub designer: Option<String>,
+    /// License description, usually a summary of the terms.
+    pub license: Option<String>,
+    pub license_url: Option<String>,
+    pub copyright: Option<String>,
+    /// Font units per em square, 0 for bitmap only fonts.
+    pub units_per_em: u16,
+    /// Whether the font has outlines.
+    pub scalable: bool,
+    /// Whether all glyphs have the same advance.
+    pub fixed_width: bool,
+    /// Whether the font has color glyphs.
+    pub color: bool,
+    /// Whether the font has kerning pairs freetype can read, from its `kern`
+    /// table.
+    pub kerning: bool,
+}
+
+/// Name table entry of `face` decoded to UTF-8.
+struct Name{
+    id: u16,
+    /// Lower is preferred when several entries have the same id.
+    rank: u8,
+    value: String,
+}
+
+unsafe fn names(face: ffi::FT_Face) -> Vec<Name>{
+    let mut names = vec![];
+    for i in 0 .. ffi::FT_Get_Sfnt_Name_Count(face) {
+        let mut name = mem::zeroed::<ffi::FT_SfntName>();
+        if ffi::FT_Get_Sfnt_Name(face, i, &mut name) != 0 || name.string.is_null() {
+            continue
+        }
+        let bytes = slice::from_raw_parts(name.string, name.string_len as usize);
+        if let Some((rank, value)) = decode(name.platform_id, name.encoding_id, name.language_id, bytes) {
+            names.push(Name{ id: name.name_id, rank, value });
+        }
+    }
+    names
+}
+
+/// Rank and value of a name entry, `None` for platforms and encodings that
+/// aren't supported.
+fn decode(platform_id: u16, encoding_id: u16, language_id: u16, bytes: &[u8]) -> Option<(u8, String)>{
+    match platform_id {
+        PLATFORM_WINDOWS if language_id == LANGUAGE_EN_US => Some((0, utf16_be(bytes))),
+        PLATFORM_WINDOWS => Some((1, utf16_be(bytes))),
+        PLATFORM_UNICODE => Some((2, utf16_be(bytes))),
+        // Roman encoding, the same as Latin-1 for the ASCII most names use
+        PLATFORM_MACINTOSH if encoding_id == 0 => Some((3, bytes.iter().map(|&b| b as char).collect())),
+        _ => None,
+    }
+}
+
+fn utf16_be(bytes: &[u8]) -> String{
+    let units = bytes.chunks(2)
+        .filter(|unit| unit.len() == 2)
+        .map(|unit| (unit[0] as u16) << 8 | unit[1] as u16)
+        .collect::<Vec<_>>();
+    String::from_utf16_lossy(&units)
+}
+
+/// Preferred non empty entry with `id`.
+fn find(names: &[Name], id: u16) -> Option<String>{
+    names.iter()
+        .filter(|name| name.id == id && !name.value.is_empty())
+        .min_by_key(|name| name.rank)
+        .map(|name| name.value.clone())
+}
+
+pub(crate) unsafe fn font_info(face: ffi::FT_Face) -> FontInfo{
+    let names = names(face);
+    let flags = (*face).face_flags;
+    let has_flag = |flag: u32| flags & flag as ffi::FT_Long != 0;
+    let postscript_name = ffi::FT_Get_Postscript_Name(face);
+    FontInfo{
+        family: find(&names, NAME_TYPOGRAPHIC_FAMILY)
+            .or_else(|| find(&names, NAME_FAMILY))
+            .unwrap_or_else(|| c_string((*face).family_name)),
+        style: find(&names, NAME_TYPOGRAPHIC_SUBFAMILY)
+            .or_else(|| find(&names, NAME_SUBFAMILY))
+            .unwrap_or_else(|| c_string((*face).style_name)),
+        postscript_name: if postscript_name.is_null() { None } else { Some(c_string(postscript_name)) },
+        version: find(&names, NAME_VERSION),
+        designer: find(&names, NAME_DESIGNER),
+        license: find(&names, NAME_LICENSE),
+        license_url: find(&names, NAME_LICENSE_URL),
+        copyright: find(&names, NAME_COPYRIGHT),
+        units_per_em: (*face).units_per_EM,
+        scalable: has_flag(ffi::FT_FACE_FLAG_SCALABLE),
+        fixed_width: has_flag(ffi::FT_FACE_FLAG_FIXED_WIDTH),
+        color: has_flag(ffi::FT_FACE_FLAG_COLOR),
+        kerning: has_flag(ffi::FT_FACE_FLAG_KERNING),
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn name(id: u16, platform_id: u16, encoding_id: u16, language_id: u16, bytes: &[u8]) -> Name{
+        let (rank, value) = decode(platform_id, encoding_id, language_id, bytes).unwrap();
+        Name{ id, rank, value }
+    }
+
+    #[test]
+    fn utf16_be_names(){
+        assert_eq!(utf16_be(&[0, b'S', 0, b'a', 0, b'n', 0, b's']), "Sans");
+        assert_eq!(utf16_be(&[0x00, 0xE9, 0x4E, 0x2D]), "é中");
+        // surrogate pair of U+1F600
+        assert_eq!(utf16_be(&[0xD8, 0x3D, 0xDE, 0x00]), "\u{1F600}");
+        // a trailing odd byte is dropped
+        assert_eq!(utf16_be(&[0, b'A', 0]), "A");
+        assert_eq!(utf16_be(&[0xD8, 0x3D]), "\u{FFFD}");
+    }
+
+    #[test]
+    fn decode_platforms(){
+        assert_eq!(decode(PLATFORM_WINDOWS, 1, LANGUAGE_EN_US, &[0, b'A']), Some((0, "A".to_owned())));
+        assert_eq!(decode(PLATFORM_WINDOWS, 1, 0x40C, &[0, b'A']), Some((1, "A".to_owned())));
+        assert_eq!(decode(PLATFORM_UNICODE, 3, 0, &[0, b'A']), Some((2, "A".to_owned())));
+        assert_eq!(decode(PLATFORM_MACINTOSH, 0, 0, &[b'A', 0xE9]), Some((3, "A\u{E9}".to_owned())));
+        assert_eq!(decode(PLATFORM_MACINTOSH, 1, 0, b"A"), None);
+        assert_eq!(decode(2, 0, 0, b"A"), None);
+    }
+
+    #[test]
+    fn find_preferred_name(){
+        let names = [
+            name(NAME_FAMILY, PLATFORM_MACINTOSH, 0, 0, b"Mac"),
+            name(NAME_FAMILY, PLATFORM_UNICODE, 3, 0, &[0, b'U']),
+            name(NAME_FAMILY, PLATFORM_WINDOWS, 1, 0x40C, &[0, b'F']),
+            name(NAME_FAMILY, PLATFORM_WINDOWS, 1, LANGUAGE_EN_US, &[0, b'E']),
+            name(NAME_VERSION, PLATFORM_WINDOWS, 1, LANGUAGE_EN_US, &[]),
+            name(NAME_VERSION, PLATFORM_MACINTOSH, 0, 0, b"1.0"),
+        ];
+        assert_eq!(find(&names, NAME_FAMILY), Some("E".to_owned()));
+        assert_eq!(find(&names[..3], NAME_FAMILY), Some("F".to_owned()));
+        assert_eq!(find(&names[..2], NAME_FAMILY), Some("U".to_owned()));
+        assert_eq!(find(&names[..1], NAME_FAMILY), Some("Mac".to_owned()));
+        // empty entries are skipped
+        assert_eq!(find(&names, NAME_VERSION), Some("1.0".to_owned()));
+        assert_eq!(find(&names, NAME_DESIGNER), None);
+    }
+}
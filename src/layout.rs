@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+
+use {is_variation_selector, Tessellation, TextureFont, TextureGlyph, Units, VertexBatch};
+
+/// A glyph placed by a `Layout`.
+pub struct PositionedGlyph{
+    pub glyph: TextureGlyph,
+    /// Pen position (in pixels) of the glyph origin on the baseline.
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Rectangle covered by a laid out glyph and its texture coordinates in the
+/// atlas. Coordinates are in pixels with y growing upwards, as for the glyph
+/// offsets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphQuad{
+    pub x0: f32,
+    /// Top edge.
+    pub y0: f32,
+    pub x1: f32,
+    /// Bottom edge.
+    pub y1: f32,
+    pub s0: f32,
+    pub t0: f32,
+    pub s1: f32,
+    pub t1: f32,
+    /// Whether the glyph was rendered for LCD screens. In that case the red,
+    /// green and blue channels of the atlas hold the coverage of each
+    /// subpixel, in that order, and have to be used as per-channel alpha
+    /// through dual-source blending instead of as a color.
+    pub lcd: bool,
+    /// Whether the atlas holds the glyph's own premultiplied colors, to be
+    /// drawn as they are instead of tinted by the text color.
+    pub colored: bool,
+}
+
+/// Characters of `text` with the variation selector following each of
+/// them. Selectors that don't follow a character are dropped.
+fn clusters<'a>(text: &'a str) -> impl Iterator<Item = (char, Option<char>)> + 'a{
+    let mut chars = text.chars().peekable();
+    std::iter::from_fn(move || {
+        loop {
+            let c = chars.next()?;
+            if is_variation_selector(c) {
+                continue
+            }
+            let selector = chars.next_if(|&selector| is_variation_selector(selector));
+            return Some((c, selector))
+        }
+    })
+}
+
+/// Pen position of a glyph after the previous one ended at `pen_x`, with
+/// the pair `kerning` between them, `None` for the first glyph.
+fn pen_position(pen_x: f32, kerning: Option<f32>, tracking: f32, letter_spacing: f32) -> f32{
+    match kerning {
+        Some(kerning) => pen_x + kerning + tracking + letter_spacing,
+        None => pen_x,
+    }
+}
+
+fn quad(positioned: &PositionedGlyph, lcd: bool) -> GlyphQuad{
+    let glyph = &positioned.glyph;
+    let x0 = positioned.x + glyph.offset_x() as f32;
+    let y0 = positioned.y + glyph.offset_y() as f32;
+    GlyphQuad{
+        x0,
+        y0,
+        x1: x0 + glyph.width() as f32,
+        y1: y0 - glyph.height() as f32,
+        s0: glyph.s0(),
+        t0: glyph.t0(),
+        s1: glyph.s1(),
+        t1: glyph.t1(),
+        lcd,
+        colored: glyph.colored(),
+    }
+}
+
+/// Lays out spans of text on a single baseline, applying pair kerning,
+/// per-span letter spacing and optionally the face's track kerning.
+pub struct Layout<'a>{
+    font: &'a TextureFont,
+    tracking: f32,
+    pen_x: f32,
+    prev: Option<u32>,
+    glyphs: Vec<PositionedGlyph>,
+    /// Unsnapped pen position of each glyph.
+    pens: Vec<f32>,
+}
+
+impl<'a> Layout<'a>{
+    pub fn new(font: &'a TextureFont) -> Layout<'a>{
+        Layout{
+            font,
+            tracking: 0.,
+            pen_x: 0.,
+            prev: None,
+            glyphs: vec![],
+            pens: vec![],
+        }
+    }
+
+    /// Apply the face's track kerning for the given degree of tightness
+    /// between every pair of glyphs, see `TextureFont::track_kerning`.
+    pub fn track_kerning(mut self, degree: i32) -> Layout<'a>{
+        self.tracking = self.font.track_kerning(degree);
+        self
+    }
+
+    /// Append a span of text. `letter_spacing` (in pixels) is added between
+    /// this span's glyphs and the glyph preceding each of them, on top of
+    /// pair kerning and tracking. Variation selectors pick the variant of the
+    /// character they follow and aren't drawn.
+    pub fn push_span(&mut self, text: &str, letter_spacing: f32) -> &mut Layout<'a>{
+        for (c, selector) in clusters(text) {
+            let glyph_id = match selector {
+                Some(selector) => self.font.char_variant_index(c, selector),
+                None => self.font.char_index(c),
+            };
+            let kerning = self.prev.map(|prev| {
+                if self.font.kerning() != 0 { self.font.kerning_by_id(prev, glyph_id) } else { 0. }
+            });
+            let pen_x = pen_position(self.pen_x, kerning, self.tracking, letter_spacing);
+            // With subpixel positioning this picks the variant closest to the pen
+            let positioned = match selector {
+                Some(selector) => self.font.glyph_variant_at(c, selector, pen_x),
+                None => self.font.glyph_at(c, pen_x),
+            };
+            let (glyph, x) = match positioned {
+                Some(positioned) => positioned,
+                None => continue,
+            };
+            let advance = glyph.advance_x();
+            self.glyphs.push(PositionedGlyph{
+                glyph,
+                x,
+                y: 0.,
+            });
+            self.pens.push(pen_x);
+            self.pen_x = pen_x + advance;
+            self.prev = Some(glyph_id);
+        }
+        self
+    }
+
+    /// Glyphs laid out so far.
+    #[inline]
+    pub fn glyphs(&self) -> &[PositionedGlyph]{
+        &self.glyphs
+    }
+
+    /// Quads to draw the glyphs laid out so far.
+    pub fn quads<'b>(&'b self) -> impl Iterator<Item = GlyphQuad> + 'b{
+        let lcd = self.font.lcd_filter().is_some();
+        self.glyphs.iter().map(move |positioned| quad(positioned, lcd))
+    }
+
+    /// Triangles of the quads to draw the glyphs laid out so far.
+    pub fn batch(&self) -> VertexBatch{
+        let mut batch = VertexBatch::new();
+        for quad in self.quads() {
+            batch.push_quad(&quad);
+        }
+        batch
+    }
+
+    /// Triangles of the glyphs laid out so far tessellated from their
+    /// outlines in pixels, to be drawn at any scale without the atlas.
+    pub fn mesh_batch(&self, tessellation: Tessellation) -> VertexBatch{
+        let mut meshes = HashMap::new();
+        let mut batch = VertexBatch::new();
+        for (positioned, &pen_x) in self.glyphs.iter().zip(&self.pens) {
+            let glyph_id = positioned.glyph.glyph_id();
+            let mesh = meshes.entry(glyph_id).or_insert_with(|| {
+                self.font.glyph_mesh(glyph_id, Units::Pixels, tessellation)
+            });
+            if let Some(ref mesh) = *mesh {
+                batch.append(mesh, pen_x, positioned.y);
+            }
+        }
+        batch
+    }
+
+    /// Pen position after the last glyph, which is the width of the laid out
+    /// text.
+    #[inline]
+    pub fn advance(&self) -> f32{
+        self.pen_x
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use std::mem;
+
+    use super::*;
+    use ffi;
+    use VertexKind;
+
+    fn positioned(glyph: &mut ffi::texture_glyph_t, colored: bool, x: f32, y: f32) -> PositionedGlyph{
+        PositionedGlyph{ glyph: TextureGlyph{ glyph, colored }, x, y }
+    }
+
+    fn glyph() -> ffi::texture_glyph_t{
+        let mut glyph = unsafe{ mem::zeroed::<ffi::texture_glyph_t>() };
+        glyph.width = 8;
+        glyph.height = 10;
+        glyph.offset_x = 1;
+        glyph.offset_y = 9;
+        glyph.s0 = 0.25;
+        glyph.t0 = 0.5;
+        glyph.s1 = 0.375;
+        glyph.t1 = 0.75;
+        glyph
+    }
+
+    #[test]
+    fn clusters_consume_selectors(){
+        let text = "a\u{FE0F}b\u{FE0E}\u{FE0F}\u{FE0F}c";
+        assert_eq!(clusters(text).collect::<Vec<_>>(), vec![
+            ('a', Some('\u{FE0F}')),
+            ('b', Some('\u{FE0E}')),
+            ('c', None),
+        ]);
+        assert_eq!(clusters("\u{E0100}x").collect::<Vec<_>>(), vec![('x', None)]);
+        assert_eq!(clusters("").count(), 0);
+    }
+
+    #[test]
+    fn pen_advance(){
+        assert_eq!(pen_position(0., None, 1., 2.), 0.);
+        assert_eq!(pen_position(10., Some(0.), 0., 0.), 10.);
+        assert_eq!(pen_position(10., Some(-1.5), 0.5, 2.), 11.);
+
+        // three glyphs advancing 10 with tracking 0.5, letter spacing 1 and
+        // kerning -2 between the last two
+        let mut pen = 0.;
+        let mut pens = vec![];
+        for &kerning in &[None, Some(0.), Some(-2.)] {
+            let x = pen_position(pen, kerning, 0.5, 1.);
+            pens.push(x);
+            pen = x + 10.;
+        }
+        assert_eq!(pens, vec![0., 11.5, 21.]);
+        assert_eq!(pen, 31.);
+    }
+
+    #[test]
+    fn quads(){
+        let mut data = glyph();
+        let quad = quad(&positioned(&mut data, false, 3., 2.), false);
+        assert_eq!(quad, GlyphQuad{
+            x0: 4., y0: 11., x1: 12., y1: 1.,
+            s0: 0.25, t0: 0.5, s1: 0.375, t1: 0.75,
+            lcd: false,
+            colored: false,
+        });
+
+        let mut data = glyph();
+        let lcd = super::quad(&positioned(&mut data, false, 0., 0.), true);
+        assert!(lcd.lcd && !lcd.colored);
+        let mut data = glyph();
+        let colored = super::quad(&positioned(&mut data, true, 0., 0.), false);
+        assert!(!colored.lcd && colored.colored);
+    }
+
+    #[test]
+    fn batch_of_quads(){
+        let mut data = glyph();
+        let plain = quad(&positioned(&mut data, false, 0., 0.), false);
+        let mut data = glyph();
+        let colored = quad(&positioned(&mut data, true, 20., 0.), false);
+        let mut batch = VertexBatch::new();
+        batch.push_quad(&plain);
+        batch.push_quad(&colored);
+
+        assert_eq!(batch.vertices.len(), 8);
+        assert_eq!(batch.indices, vec![0, 3, 2, 0, 2, 1, 4, 7, 6, 4, 6, 5]);
+        assert_eq!(batch.vertices[0].position, [1., 9.]);
+        assert_eq!(batch.vertices[0].uv, [0.25, 0.5]);
+        assert_eq!(batch.vertices[2].position, [9., -1.]);
+        assert_eq!(batch.vertices[2].uv, [0.375, 0.75]);
+        assert!(batch.vertices[..4].iter().all(|v| v.kind == VertexKind::Atlas));
+        assert!(batch.vertices[4..].iter().all(|v| v.kind == VertexKind::ColoredAtlas));
+        assert_eq!(batch.vertices[4].position, [21., 9.]);
+    }
+}
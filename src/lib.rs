@@ -1,27 +1,129 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::ffi::CString;
-use std::os::raw::{c_char, c_void};
+use std::fmt;
+use std::os::raw::c_char;
 use std::mem;
 use std::slice;
-use std::ptr;
 
 mod ffi;
+mod batch;
+mod builder;
+mod collection;
+#[cfg(feature = "color")]
+mod color;
+mod coverage;
+mod embedding;
+mod info;
+mod layout;
+mod mesh;
+mod metrics;
+mod msdf;
+mod outline;
+mod raster;
+mod sdf;
+mod shape;
+mod stroke;
+mod variation;
 
 #[cfg(all(debug_assertions, windows))]
 mod link_windowsd;
 #[cfg(any(not(debug_assertions), not(windows)))]
 mod link;
 
+pub use batch::{Vertex, VertexBatch, VertexKind};
+pub use builder::TextureFontBuilder;
+pub use collection::{faces_in_file, faces_in_memory, FaceEntry};
+#[cfg(feature = "color")]
+pub use color::Palette;
+pub use coverage::{CharSet, Language, LanguageSupport, LANGUAGES};
+pub use embedding::{AtlasExport, EmbeddingAction, EmbeddingLicense, EmbeddingPermissions, EmbeddingPolicy, EmbeddingRestriction};
+pub use info::FontInfo;
+pub use layout::{GlyphQuad, Layout, PositionedGlyph};
+pub use mesh::Tessellation;
+pub use metrics::{MetricsSource, VerticalMetrics};
+pub use outline::{ControlBox, Contour, Outline, PathCommand, Units, Winding};
+pub use raster::FixedSize;
+pub use sdf::SdfOptions;
+pub use stroke::{LineCap, LineJoin, StrokeOptions};
+pub use variation::VariationAxis;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error{
+    /// The texture atlas couldn't be allocated.
+    AtlasCreation,
+    /// freetype couldn't open the font.
+    FontCreation,
+    /// The atlas pixel format can't hold glyphs rendered with the requested
+    /// options, for the given reason.
+    IncompatiblePixelFormat(PixelFormat, &'static str),
+    /// freetype-gl returned an unknown render mode.
+    InvalidRenderMode(u32),
+    /// The font can't be rendered in the requested mode, for the given
+    /// reason.
+    UnsupportedRenderMode(RenderMode, &'static str),
+    /// The font file has no face at this index.
+    InvalidFaceIndex(usize),
+    /// The font has no fixed size strike at this index.
+    InvalidFixedSize(usize),
+    /// The embedding policy of the font refuses to export it with this
+    /// restriction.
+    EmbeddingRestricted(EmbeddingRestriction),
+}
+
+impl fmt::Display for Error{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result{
+        match *self {
+            Error::AtlasCreation => write!(f, "Couldn't allocate the texture atlas"),
+            Error::FontCreation => write!(f, "Couldn't load the font"),
+            Error::IncompatiblePixelFormat(format, reason) => write!(f, "Can't use a {:?} atlas: {}", format, reason),
+            Error::InvalidRenderMode(mode) => write!(f, "Unknown render mode {}", mode),
+            Error::UnsupportedRenderMode(mode, reason) => write!(f, "Can't render glyphs as {:?}: {}", mode, reason),
+            Error::InvalidFaceIndex(index) => write!(f, "The font has no face {}", index),
+            Error::InvalidFixedSize(index) => write!(f, "The font has no fixed size {}", index),
+            Error::EmbeddingRestricted(restriction) => write!(f, "The font can't be embedded: {:?}", restriction),
+        }
+    }
+}
+
+impl std::error::Error for Error{}
+
 pub struct TextureFont{
     font: *mut ffi::texture_font_t,
     bytes: Option<Vec<u8>>,
+    raster: raster::RasterOptions,
+    /// Validated by the builder, which set it in freetype-gl.
+    rendermode: RenderMode,
+    /// Checked when the atlas is exported.
+    embedding_policy: Option<EmbeddingPolicy>,
+    /// Glyphs rasterized from rust.
+    glyphs: RefCell<HashMap<raster::GlyphKey, TextureGlyph>>,
+    /// Design axes of variable fonts.
+    axes: Vec<variation::VariationAxis>,
+    /// Every set of design coordinates glyphs were rasterized with.
+    instances: Vec<Vec<ffi::FT_Fixed>>,
+    /// Index of the current design coordinates in `instances`.
+    instance: u16,
 }
 
 impl Drop for TextureFont{
     fn drop(&mut self){
-        unsafe{ ffi::texture_font_delete(self.font) }
+        unsafe{
+            for (_, glyph) in self.glyphs.borrow_mut().drain() {
+                ffi::texture_glyph_delete(glyph.glyph);
+            }
+            ffi::texture_font_delete(self.font)
+        }
     }
 }
 
+/// Whether `c` is one of the variation selectors VS1 to VS256, which pick a
+/// variant of the character they follow.
+pub fn is_variation_selector(c: char) -> bool{
+    matches!(c, '\u{FE00}' ..= '\u{FE0F}' | '\u{E0100}' ..= '\u{E01EF}')
+}
+
 unsafe fn char_to_utf8(c: char) -> Vec<u8>{
     let c = c.to_string();
     CString::from_vec_unchecked(c.as_bytes().to_vec()).as_bytes().to_vec()
@@ -29,69 +131,88 @@ unsafe fn char_to_utf8(c: char) -> Vec<u8>{
 
 impl TextureFont{
 	pub fn load(path: &str, pt_size: f32, depth: usize) -> Option<TextureFont>{
-        unsafe{
-            let tex_atlas = ffi::texture_atlas_new(512, 512, depth as u64);
-            if tex_atlas == ptr::null_mut() {
-                return None
-            }
-
-            // println!("allocated tex atlas  {},{}x{}", (*tex_atlas).width, (*tex_atlas).height, (*tex_atlas).depth);
-            let path = CString::new(path.as_bytes()).unwrap();
-            let c_path = path.as_ptr();
-            let tex_font = ffi::texture_font_new_from_file( tex_atlas, pt_size, c_path);
-            if tex_font == ptr::null_mut() {
-                return None
-            }
-
-            let latin1 = (32 as u8 .. 255).collect::<Vec<_>>();
-            let glyphs_i32 = CString::new(latin1).unwrap();
-            ffi::texture_font_load_glyphs(tex_font, glyphs_i32.into_raw());
-            // println!("loaded {} glyphs", ffi::vector_size((*tex_font).glyphs));
-
-            Some(TextureFont{
-                font: tex_font,
-                bytes: None,
-            })
-        }
+        let format = PixelFormat::from_depth(depth)?;
+        TextureFontBuilder::from_file(path, pt_size, format).build().ok()
 	}
 
 	pub fn load_from_memory(font_data: Vec<u8>, pt_size: f32, depth: usize) -> Option<TextureFont>{
-        unsafe{
-            let tex_atlas = ffi::texture_atlas_new(512,512, depth as u64);
-            if tex_atlas == ptr::null_mut() {
-                return None
-            }
-
-            // println!("allocated tex atlas  {},{}x{}", (*tex_atlas).width, (*tex_atlas).height, (*tex_atlas).depth);
-            let tex_font = ffi::texture_font_new_from_memory(
-                tex_atlas,
-                pt_size,
-                font_data.as_ptr() as *const c_void,
-                font_data.len() as u64);
-            if tex_font == ptr::null_mut() {
-                return None
-            }
+        let format = PixelFormat::from_depth(depth)?;
+        TextureFontBuilder::from_memory(font_data, pt_size, format).build().ok()
+	}
 
+    fn load_latin1(&self){
+        if self.raster.freetype_gl_compatible() {
             let latin1 = (32 as u8 .. 255).collect::<Vec<_>>();
             let glyphs_i32 = CString::new(latin1).unwrap();
-            ffi::texture_font_load_glyphs(tex_font, glyphs_i32.into_raw());
+            unsafe{ ffi::texture_font_load_glyphs(self.font, glyphs_i32.as_ptr()) };
+        }else{
+            for c in (32 as u8 .. 255).map(char::from) {
+                self.glyph(c);
+            }
+        }
+    }
+
+    /// Freetype glyph id for `c`.
+    #[inline]
+    pub(crate) fn char_index(&self, c: char) -> u32{
+        unsafe{ ffi::FT_Get_Char_Index(self.face(), c as ffi::FT_ULong) }
+    }
 
-            // println!("loaded {} glyphs", ffi::vector_size((*tex_font).glyphs));
+    /// Cache key of the glyph `glyph_id` at the subpixel `phase` with the
+    /// current design coordinates.
+    #[inline]
+    fn key(&self, glyph_id: u32, phase: u8) -> raster::GlyphKey{
+        raster::GlyphKey{ glyph_id, phase, instance: self.instance }
+    }
 
-            Some(TextureFont{
-                font: tex_font,
-                bytes: Some(font_data),
-            })
+    /// Loads a glyph through the rust rasterizer, caching it by glyph id,
+    /// subpixel phase and design coordinates.
+    fn rasterized_glyph(&self, codepoint: u32, key: raster::GlyphKey) -> Option<TextureGlyph>{
+        let mut glyphs = self.glyphs.borrow_mut();
+        if let Some(glyph) = glyphs.get(&key) {
+            return Some(glyph.clone())
         }
-	}
+        unsafe{
+            let glyph = raster::load_glyph(self.font, codepoint, key, &self.raster)?;
+            // Kerning pairs are looked up by codepoint in the right glyph, so
+            // the new glyph needs one pair per left glyph and codepoint, and
+            // every subpixel variant of the other glyphs needs the pair with
+            // the new glyph unless a variant of it already added it
+            let same = |other: *mut ffi::texture_glyph_t| {
+                (*other).glyph_id == key.glyph_id && (*other).codepoint == codepoint
+            };
+            let others = glyphs.iter()
+                .filter(|&(k, _)| k.instance == key.instance)
+                .map(|(_, other)| other.glyph)
+                .collect::<Vec<_>>();
+            let mut lefts = HashMap::new();
+            for &other in others.iter().filter(|&&other| !same(other)) {
+                lefts.entry(((*other).glyph_id, (*other).codepoint)).or_insert(other);
+            }
+            let lefts = lefts.values().cloned().collect::<Vec<_>>();
+            let rights = if others.iter().any(|&other| same(other)) {
+                vec![]
+            }else{
+                others
+            };
+            raster::generate_kerning(self.face(), glyph.glyph, &lefts, &rights);
+            glyphs.insert(key, glyph.clone());
+            Some(glyph)
+        }
+    }
 
     #[inline]
 	pub fn glyph(&self, c: char) -> Option<TextureGlyph>{
+        if !self.raster.freetype_gl_compatible() {
+            let glyph_id = self.char_index(c);
+            return self.rasterized_glyph(c as u32, self.key(glyph_id, 0))
+        }
         unsafe{
             let glyph = ffi::texture_font_get_glyph(self.font, char_to_utf8(c).as_ptr() as *const c_char);
-            if glyph != ptr::null_mut() {
+            if !glyph.is_null() {
                 Some(TextureGlyph{
-                    glyph
+                    glyph,
+                    colored: false,
                 })
             }else{
                 None
@@ -101,11 +222,15 @@ impl TextureFont{
 
     #[inline]
 	pub fn glyph_by_freetype_id(&self, glyph_id: u32) -> Option<TextureGlyph>{
+        if !self.raster.freetype_gl_compatible() {
+            return self.rasterized_glyph(0, self.key(glyph_id, 0))
+        }
         unsafe{
             let glyph = ffi::texture_font_get_glyph_by_id(self.font, glyph_id);
-            if glyph != ptr::null_mut() {
+            if !glyph.is_null() {
                 Some(TextureGlyph{
-                    glyph
+                    glyph,
+                    colored: false,
                 })
             }else{
                 None
@@ -113,22 +238,290 @@ impl TextureFont{
         }
 	}
 
+    /// Id of the glyph named `name` in the font, eg. "home" in icon fonts.
+    /// `None` if the font has no glyph names or no glyph with this one.
+    pub fn glyph_id_by_name(&self, name: &str) -> Option<u32>{
+        let name = CString::new(name).ok()?;
+        match unsafe{ ffi::FT_Get_Name_Index(self.face(), name.as_ptr()) } {
+            0 => None,
+            glyph_id => Some(glyph_id),
+        }
+    }
+
+    /// Glyph named `name`, loaded into the atlas like `glyph_by_freetype_id`.
+    pub fn glyph_by_name(&self, name: &str) -> Option<TextureGlyph>{
+        self.glyph_id_by_name(name).and_then(|glyph_id| self.glyph_by_freetype_id(glyph_id))
+    }
+
+    /// PostScript name of the glyph `glyph_id`, `None` if the font has no
+    /// glyph names.
+    pub fn glyph_name(&self, glyph_id: u32) -> Option<String>{
+        unsafe{
+            if (*self.face()).face_flags & ffi::FT_FACE_FLAG_GLYPH_NAMES as ffi::FT_Long == 0 {
+                return None
+            }
+            // Names are at most 63 characters long in Type 1 and CFF fonts
+            let mut buffer = [0u8; 256];
+            if ffi::FT_Get_Glyph_Name(self.face(), glyph_id, buffer.as_mut_ptr() as ffi::FT_Pointer, buffer.len() as ffi::FT_UInt) != 0 {
+                return None
+            }
+            let len = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+            if len == 0 {
+                None
+            }else{
+                Some(String::from_utf8_lossy(&buffer[..len]).into_owned())
+            }
+        }
+    }
+
+    /// Glyph for `c` to be drawn with its origin at the pen position `x`.
+    ///
+    /// When subpixel positioning is enabled, the returned glyph is the variant
+    /// rasterized at the subpixel phase nearest to `x`, and the returned
+    /// position is the whole pixel it has to be drawn at. Otherwise this is
+    /// the same as `glyph` and `x` is returned unchanged.
+    pub fn glyph_at(&self, c: char, x: f32) -> Option<(TextureGlyph, f32)>{
+        if self.raster.subpixel_phases < 2 {
+            return self.glyph(c).map(|glyph| (glyph, x))
+        }
+        let glyph_id = self.char_index(c);
+        let (x, phase) = self.raster.snap(x);
+        self.rasterized_glyph(c as u32, self.key(glyph_id, phase))
+            .map(|glyph| (glyph, x))
+    }
+
+    /// Same as `glyph_at` for a glyph given by its freetype id.
+    pub fn glyph_by_freetype_id_at(&self, glyph_id: u32, x: f32) -> Option<(TextureGlyph, f32)>{
+        if self.raster.subpixel_phases < 2 {
+            return self.glyph_by_freetype_id(glyph_id).map(|glyph| (glyph, x))
+        }
+        let (x, phase) = self.raster.snap(x);
+        self.rasterized_glyph(0, self.key(glyph_id, phase))
+            .map(|glyph| (glyph, x))
+    }
+
+    /// Freetype glyph id for `c` followed by the variation selector
+    /// `selector`, or for `c` alone if the font has no such variant.
+    pub fn char_variant_index(&self, c: char, selector: char) -> u32{
+        let glyph_id = unsafe{
+            ffi::FT_Face_GetCharVariantIndex(self.face(), c as ffi::FT_ULong, selector as ffi::FT_ULong)
+        };
+        if glyph_id != 0 {
+            glyph_id
+        }else{
+            self.char_index(c)
+        }
+    }
+
+    /// Glyph for `c` followed by the variation selector `selector`, eg. the
+    /// emoji or text presentation of a symbol or an ideographic variant.
+    /// Falls back to the glyph for `c` if the font has no such variant.
+    pub fn glyph_variant(&self, c: char, selector: char) -> Option<TextureGlyph>{
+        let glyph_id = self.char_variant_index(c, selector);
+        if glyph_id == self.char_index(c) {
+            return self.glyph(c)
+        }
+        // Variants are always rasterized from rust, freetype-gl looks glyphs
+        // up by codepoint and would return the variant for `c` alone
+        self.rasterized_glyph(c as u32, self.key(glyph_id, 0))
+    }
+
+    /// Same as `glyph_at` for `c` followed by the variation selector
+    /// `selector`.
+    pub fn glyph_variant_at(&self, c: char, selector: char, x: f32) -> Option<(TextureGlyph, f32)>{
+        if self.raster.subpixel_phases < 2 {
+            return self.glyph_variant(c, selector).map(|glyph| (glyph, x))
+        }
+        let glyph_id = self.char_variant_index(c, selector);
+        let (x, phase) = self.raster.snap(x);
+        self.rasterized_glyph(c as u32, self.key(glyph_id, phase))
+            .map(|glyph| (glyph, x))
+    }
+
+    /// Variation selectors the font has variants for.
+    pub fn variation_selectors(&self) -> Vec<char>{
+        let mut selectors = vec![];
+        unsafe{
+            let mut selector = ffi::FT_Face_GetVariantSelectors(self.face());
+            while !selector.is_null() && *selector != 0 {
+                selectors.extend(std::char::from_u32(*selector));
+                selector = selector.offset(1);
+            }
+        }
+        selectors
+    }
+
+    /// Names, license and flags of the font.
+    pub fn info(&self) -> FontInfo{
+        unsafe{ info::font_info(self.face()) }
+    }
+
+    /// Codepoints the font has a glyph for.
+    pub fn coverage(&self) -> CharSet{
+        unsafe{ CharSet::from_face(self.face()) }
+    }
+
+    /// Embedding permissions of the font from its OS/2 table.
+    pub fn embedding_permissions(&self) -> EmbeddingPermissions{
+        unsafe{ embedding::permissions(self.face()) }
+    }
+
+    /// Atlas to bake into files shipped with documents, once the embedding
+    /// policy the font was built with allows it. Fonts built without one are
+    /// always allowed.
+    pub fn atlas_for_export(&self) -> Result<AtlasExport, Error>{
+        let warnings = match self.embedding_policy {
+            Some(ref policy) => policy.check(&self.embedding_permissions())?,
+            None => vec![],
+        };
+        Ok(AtlasExport{ atlas: self.atlas(), warnings })
+    }
+
+    /// Design axes of the font, empty if it isn't a variable font.
+    #[inline]
+    pub fn variation_axes(&self) -> &[VariationAxis]{
+        &self.axes
+    }
+
+    /// Current value of each design axis, in the order of `variation_axes`.
+    pub fn variations(&self) -> Vec<f32>{
+        self.instances[self.instance as usize].iter().map(|&c| c as f32 / 65536.).collect()
+    }
+
+    /// Sets the design axis `tag`, eg. `b"wght"`, to `value` clamped to the
+    /// axis range. Glyphs are rasterized again for each new set of
+    /// coordinates, the ones already in the atlas are kept for when they are
+    /// set again. The ascender, descender, height and line gap follow the
+    /// new coordinates. Returns false if the font has no such axis.
+    pub fn set_variation(&mut self, tag: &[u8; 4], value: f32) -> bool{
+        let mut coordinates = self.instances[self.instance as usize].clone();
+        if !variation::set(&self.axes, &mut coordinates, tag, value)
+            || !unsafe{ variation::apply(self.face(), &mut coordinates) }
+        {
+            return false
+        }
+        unsafe{ builder::update_line_metrics(&mut *self.font) };
+        self.instance = match self.instances.iter().position(|c| *c == coordinates) {
+            Some(instance) => instance as u16,
+            None => {
+                self.instances.push(coordinates);
+                self.instances.len() as u16 - 1
+            }
+        };
+        true
+    }
+
+    /// Number of horizontal subpixel positions glyphs are rasterized at, 1 if
+    /// subpixel positioning is disabled.
+    #[inline]
+    pub fn subpixel_positions(&self) -> usize{
+        (self.raster.subpixel_phases as usize).max(1)
+    }
+
+    /// Vector outline of the glyph `glyph_id` in `units`, without synthetic
+    /// styles applied. `None` if it can't be loaded or the font has no
+    /// outlines.
+    pub fn outline(&self, glyph_id: u32, units: Units) -> Option<Outline>{
+        let (flags, scale) = match units {
+            Units::FontUnits => (ffi::FT_LOAD_NO_SCALE as ffi::FT_Int32, 1.),
+            Units::Pixels => (self.raster.hinting.load_flags(), 1. / 64.),
+        };
+        unsafe{
+            let face = self.face();
+            if ffi::FT_Load_Glyph(face, glyph_id, flags | ffi::FT_LOAD_NO_BITMAP as ffi::FT_Int32) != 0 {
+                return None
+            }
+            let slot = (*face).glyph;
+            if (*slot).format != ffi::FT_Glyph_Format__FT_GLYPH_FORMAT_OUTLINE {
+                return None
+            }
+            let shape = shape::Shape::from_outline(&mut (*slot).outline, scale);
+            Some(Outline::from_shape(&shape))
+        }
+    }
+
+    /// Triangle mesh of the glyph `glyph_id` in `units`, to draw it at any
+    /// scale without the atlas.
+    pub fn glyph_mesh(&self, glyph_id: u32, units: Units, tessellation: Tessellation) -> Option<VertexBatch>{
+        self.outline(glyph_id, units).map(|outline| outline.tessellate(tessellation))
+    }
+
+    /// Kerning (in fractional pixels) to apply between two glyphs given by
+    /// their freetype ids, as returned by `TextureGlyph::glyph_id`.
+    pub fn kerning_by_id(&self, left_glyph_id: u32, right_glyph_id: u32) -> f32{
+        unsafe{
+            let mut kerning = mem::zeroed::<ffi::FT_Vector>();
+            let error = ffi::FT_Get_Kerning(
+                self.face(),
+                left_glyph_id,
+                right_glyph_id,
+                ffi::FT_Kerning_Mode__FT_KERNING_UNFITTED,
+                &mut kerning);
+            if error != 0 {
+                return 0.
+            }
+            kerning.x as f32 / 64.
+        }
+    }
+
+    /// Iterator over every glyph loaded so far.
+    pub fn glyphs<'a>(&'a self) -> impl Iterator<Item = TextureGlyph> + 'a{
+        let glyphs = unsafe{ (*self.font).glyphs_per_glyph_id };
+        let len = unsafe{ ffi::vector_size(glyphs) as usize };
+        let rasterized = self.glyphs.borrow().values().cloned().collect::<Vec<_>>();
+        (0..len).filter_map(move |i| unsafe{
+            let glyph = *(ffi::vector_get(glyphs, i as ffi::size_t) as *const *mut ffi::texture_glyph_t);
+            // skip the special glyph used for line drawing and background
+            if (*glyph).glyph_id == u32::MAX {
+                None
+            }else{
+                Some(TextureGlyph{ glyph, colored: false })
+            }
+        }).chain(rasterized)
+    }
+
+    /// Iterator over the kerning pairs between every loaded glyph, the same
+    /// ones stored in their kerning vectors, for the current design
+    /// coordinates. Pairs are keyed by glyph id, since glyphs loaded by id
+    /// have codepoint 0, and the subpixel variants and other design instances
+    /// of a glyph only count once.
+    pub fn kerning_pairs(&self) -> impl Iterator<Item = KerningPair>{
+        let mut glyphs = self.glyphs()
+            .map(|glyph| (glyph.glyph_id(), glyph.codepoint()))
+            .collect::<Vec<_>>();
+        glyphs.sort_unstable();
+        glyphs.dedup();
+
+        let mut pairs = vec![];
+        if self.kerning() != 0 {
+            for &(right_glyph_id, right) in &glyphs {
+                for &(left_glyph_id, left) in &glyphs {
+                    let kerning = self.kerning_by_id(left_glyph_id, right_glyph_id);
+                    if kerning != 0. {
+                        pairs.push(KerningPair{ left, right, left_glyph_id, right_glyph_id, kerning });
+                    }
+                }
+            }
+        }
+        pairs.into_iter()
+    }
+
     /// Font size
     #[inline]
     pub fn size(&self) -> f32{
 		unsafe{ (*self.font).size }
 	}
 
-    /// Whether to use autohint when rendering font
+    /// Hinting applied when rendering the font
     #[inline]
-    pub fn hinting(&self) -> i32{
-		unsafe{ (*self.font).hinting }
+    pub fn hinting(&self) -> Hinting{
+		self.raster.hinting
 	}
 
     /// Mode the font is rendering its next glyph
     #[inline]
     pub fn rendermode(&self) -> RenderMode{
-		unsafe{ mem::transmute((*self.font).rendermode) }
+		self.rendermode
 	}
 
     /// Outline thickness
@@ -143,6 +536,56 @@ impl TextureFont{
 		unsafe{ (*self.font).filtering }
 	}
 
+    /// Filter applied to LCD subpixel rendered glyphs, `None` if the font
+    /// isn't rendered for LCD screens.
+    #[inline]
+    pub fn lcd_filter(&self) -> Option<LcdFilter>{
+        if self.raster.format == PixelFormat::RGB8 && !self.msdf() {
+            Some(self.raster.lcd_filter)
+        }else{
+            None
+        }
+    }
+
+    /// Options of the distance fields generated from rust, `None` if glyphs
+    /// aren't rendered as distance fields or freetype-gl generates them.
+    #[inline]
+    pub fn sdf_options(&self) -> Option<SdfOptions>{
+        if self.rendermode() == RenderMode::SignedDistanceField && !self.raster.freetype_gl_compatible() {
+            Some(self.raster.sdf.unwrap_or_default())
+        }else{
+            None
+        }
+    }
+
+    /// How glyph outlines are stroked in the outline render modes.
+    #[inline]
+    pub fn stroke_options(&self) -> StrokeOptions{
+        self.raster.stroke
+    }
+
+    /// Whether glyphs hold their fill coverage in the red channel and their
+    /// outline coverage in the green one.
+    #[inline]
+    pub fn fill_and_outline(&self) -> bool{
+        self.raster.fill_and_outline
+    }
+
+    /// Whether glyphs are rendered as multi-channel distance fields, where
+    /// the distance is the median of the red, green and blue channels.
+    #[inline]
+    pub fn msdf(&self) -> bool{
+        self.raster.field_source == sdf::FieldSource::MultiChannel
+    }
+
+    /// Distance in pixels covered by the values of distance fields generated
+    /// from rust, from 0 to 1, that shaders need to scale them back to screen
+    /// pixels. `None` if glyphs aren't such distance fields.
+    #[inline]
+    pub fn distance_range(&self) -> Option<f32>{
+        self.sdf_options().map(|options| 2. * options.spread.max(1.))
+    }
+
     /// LCD filter weights
     #[inline]
     pub fn lcd_weights(&self) -> [ :: std :: os :: raw :: c_uchar ; 5usize ]{
@@ -210,9 +653,70 @@ impl TextureFont{
 		unsafe{ (*self.font).underline_thickness }
 	}
 
+    /// Fixed size bitmap strikes of the font, see
+    /// `TextureFontBuilder::fixed_sizes`.
+    pub fn fixed_sizes(&self) -> Vec<FixedSize>{
+        unsafe{ raster::fixed_sizes(self.face()) }
+    }
+
+    /// Font units per em square, 0 for bitmap only fonts.
+    #[inline]
+    pub fn units_per_em(&self) -> u16{
+        unsafe{ (*self.face()).units_per_EM }
+    }
+
+    /// Factor from font units to `units`, `None` for bitmap only fonts in
+    /// font units.
+    fn units_scale(&self, units: Units) -> Option<f32>{
+        metrics::units_scale(units, self.units_per_em(), self.size())
+    }
+
+    /// `value` in font units scaled to pixels at the font's size, unhinted.
+    /// `None` for bitmap only fonts.
+    pub fn scale_font_units(&self, value: f32) -> Option<f32>{
+        self.units_scale(Units::Pixels).map(|scale| value * scale)
+    }
+
+    /// Ascender, descender and line gap read from `source`, in `units` and
+    /// unhinted, unlike `ascender` and `descender` which are rounded to whole
+    /// pixels. `None` if the font has no such metrics.
+    pub fn vertical_metrics(&self, source: MetricsSource, units: Units) -> Option<VerticalMetrics>{
+        let scale = self.units_scale(units)?;
+        unsafe{ metrics::vertical_metrics(self.face(), source, scale) }
+    }
+
+    /// Height of lowercase letters like 'x' above the baseline in `units`,
+    /// from the OS/2 table. `None` if the font doesn't have it.
+    pub fn x_height(&self, units: Units) -> Option<f32>{
+        let scale = self.units_scale(units)?;
+        unsafe{ metrics::x_height(self.face()) }.map(|height| height * scale)
+    }
+
+    /// Height of flat capital letters like 'H' above the baseline in
+    /// `units`, from the OS/2 table. `None` if the font doesn't have it.
+    pub fn cap_height(&self, units: Units) -> Option<f32>{
+        let scale = self.units_scale(units)?;
+        unsafe{ metrics::cap_height(self.face()) }.map(|height| height * scale)
+    }
+
+    /// Track kerning in pixels for the given degree of tightness, to be
+    /// uniformly applied between all glyphs. Increasingly negative values
+    /// represent tighter tracking, positive values looser tracking. Returns 0
+    /// if the face doesn't define track kerning.
+    pub fn track_kerning(&self, degree: i32) -> f32{
+        unsafe{
+            let point_size = (self.size() * 65536.) as ffi::FT_Fixed;
+            let mut kerning: ffi::FT_Fixed = 0;
+            if ffi::FT_Get_Track_Kerning(self.face(), point_size, degree, &mut kerning) != 0 {
+                return 0.
+            }
+            kerning as f32 / 65536.
+        }
+    }
+
     #[inline]
     pub fn atlas(&self) -> TextureAtlas{
-        TextureAtlas{ atlas: unsafe{ (*self.font).atlas } }
+        TextureAtlas{ atlas: unsafe{ (*self.font).atlas }, format: self.raster.format }
     }
 
     pub unsafe fn face(&self) -> ffi::FT_Face{
@@ -221,6 +725,7 @@ impl TextureFont{
 }
 
 #[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RenderMode{
     Normal = 0,
     OutlineEdge = 1,
@@ -229,8 +734,136 @@ pub enum RenderMode{
     SignedDistanceField = 4,
 }
 
+impl RenderMode{
+    /// Whether glyphs are stroked instead of filled.
+    #[inline]
+    pub(crate) fn is_outline(self) -> bool{
+        match self {
+            RenderMode::OutlineEdge | RenderMode::OutlinePositive | RenderMode::OutlineNegatice => true,
+            RenderMode::Normal | RenderMode::SignedDistanceField => false,
+        }
+    }
+}
+
+impl TryFrom<ffi::rendermode_t> for RenderMode{
+    type Error = Error;
+    fn try_from(mode: ffi::rendermode_t) -> Result<RenderMode, Error>{
+        match mode {
+            ffi::rendermode_t_RENDER_NORMAL => Ok(RenderMode::Normal),
+            ffi::rendermode_t_RENDER_OUTLINE_EDGE => Ok(RenderMode::OutlineEdge),
+            ffi::rendermode_t_RENDER_OUTLINE_POSITIVE => Ok(RenderMode::OutlinePositive),
+            ffi::rendermode_t_RENDER_OUTLINE_NEGATIVE => Ok(RenderMode::OutlineNegatice),
+            ffi::rendermode_t_RENDER_SIGNED_DISTANCE_FIELD => Ok(RenderMode::SignedDistanceField),
+            mode => Err(Error::InvalidRenderMode(mode)),
+        }
+    }
+}
+
+/// Pixel format of a texture atlas.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PixelFormat{
+    /// 8 bit coverage or distance.
+    #[default]
+    R8,
+    /// 8 bit per channel coverage of each subpixel, for LCD rendering.
+    RGB8,
+    /// 8 bit per channel premultiplied color, glyphs without color are white.
+    /// Also holds the fill and outline coverage of
+    /// `TextureFontBuilder::fill_and_outline`.
+    RGBA8,
+    /// 32 bit float distance, for signed distance fields. The texels
+    /// freetype-gl reserves for line drawing hold 1, fully inside.
+    R32F,
+}
+
+impl PixelFormat{
+    /// Format of an atlas with `depth` bytes per pixel as interpreted by
+    /// freetype-gl.
+    pub fn from_depth(depth: usize) -> Option<PixelFormat>{
+        match depth {
+            1 => Some(PixelFormat::R8),
+            3 => Some(PixelFormat::RGB8),
+            4 => Some(PixelFormat::RGBA8),
+            _ => None,
+        }
+    }
+
+    /// Bytes per pixel.
+    pub fn depth(self) -> usize{
+        match self {
+            PixelFormat::R8 => 1,
+            PixelFormat::RGB8 => 3,
+            PixelFormat::RGBA8 | PixelFormat::R32F => 4,
+        }
+    }
+}
+
+/// How glyph outlines are fitted to the pixel grid before rendering.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Hinting{
+    /// No hinting, glyphs keep their exact outline shapes.
+    None,
+    /// Light auto-hinting, which only snaps to the pixel grid vertically.
+    /// Usually the best choice for high DPI screens.
+    Light,
+    /// Full hinting with the font's own hinter if it has one.
+    Normal,
+    /// Full hinting with freetype's auto-hinter, ignoring the font's own
+    /// hinter. This is freetype-gl's default.
+    #[default]
+    AutoHint,
+}
+
+impl Hinting{
+    /// Flags to pass to FT_Load_Glyph.
+    pub(crate) fn load_flags(self) -> ffi::FT_Int32{
+        match self {
+            Hinting::None => (ffi::FT_LOAD_NO_HINTING | ffi::FT_LOAD_NO_AUTOHINT) as ffi::FT_Int32,
+            Hinting::Light => raster::load_target(ffi::FT_Render_Mode__FT_RENDER_MODE_LIGHT),
+            Hinting::Normal => raster::load_target(ffi::FT_Render_Mode__FT_RENDER_MODE_NORMAL),
+            Hinting::AutoHint => ffi::FT_LOAD_FORCE_AUTOHINT as ffi::FT_Int32,
+        }
+    }
+}
+
+/// Filter applied to LCD subpixel rendered glyphs to reduce color fringes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LcdFilter{
+    /// No filtering, glyphs show strong color fringes.
+    None,
+    /// freetype-gl's default 5-tap filter, (0x10, 0x40, 0x70, 0x40, 0x10),
+    /// blurrier than freetype's.
+    #[default]
+    Default,
+    /// freetype's default 5-tap filter, (0x08, 0x4D, 0x56, 0x4D, 0x08).
+    FreeType,
+    /// freetype's light filter, sharper but with more color fringes.
+    Light,
+    /// freetype's legacy intra-pixel filter, as used by libXft.
+    Legacy,
+    /// Custom 5-tap filter weights in 1/256th units.
+    Custom([u8; 5]),
+}
+
+#[derive(Clone)]
 pub struct TextureGlyph{
-    glyph: *mut ffi::texture_glyph_t
+    glyph: *mut ffi::texture_glyph_t,
+    colored: bool,
+}
+
+/// A kerning pair between two glyphs as stored by freetype-gl.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KerningPair{
+    /// Unicode codepoint of the left glyph in the pair, 0 for glyphs loaded
+    /// by id.
+    pub left: u32,
+    /// Unicode codepoint of the right glyph in the pair, 0 for glyphs loaded
+    /// by id.
+    pub right: u32,
+    pub left_glyph_id: u32,
+    pub right_glyph_id: u32,
+    /// Kerning value (in fractional pixels).
+    pub kerning: f32,
 }
 
 impl std::fmt::Debug for TextureGlyph{
@@ -246,6 +879,7 @@ impl std::fmt::Debug for TextureGlyph{
             .field("t0", &self.t0())
             .field("s1", &self.s1())
             .field("t1", &self.t1())
+            .field("colored", &self.colored)
             .finish()
     }
 }
@@ -337,10 +971,18 @@ impl TextureGlyph{
         unsafe{ (*self.glyph).t1 }
     }
 
-    /// Mode this glyph was rendered
+    /// Whether the atlas holds the glyph's own premultiplied colors, which
+    /// shouldn't be tinted by the text color.
+    #[inline]
+    pub fn colored(&self) -> bool{
+        self.colored
+    }
+
+    /// Mode this glyph was rendered, `Error::InvalidRenderMode` if
+    /// freetype-gl set one this crate doesn't know.
     #[inline]
-    pub fn rendermode(&self) ->  RenderMode {
-        unsafe{ mem::transmute((*self.glyph).rendermode) }
+    pub fn rendermode(&self) -> Result<RenderMode, Error> {
+        RenderMode::try_from(unsafe{ (*self.glyph).rendermode })
     }
 
     /// Glyph outline thickness
@@ -352,7 +994,8 @@ impl TextureGlyph{
 
 
 pub struct TextureAtlas{
-    atlas: *mut ffi::texture_atlas_t
+    atlas: *mut ffi::texture_atlas_t,
+    format: PixelFormat,
 }
 
 impl TextureAtlas{
@@ -374,6 +1017,12 @@ impl TextureAtlas{
 		unsafe{ (*self.atlas).depth as usize }
 	}
 
+    /// Pixel format of the underlying texture
+    #[inline]
+    pub fn pixel_format(&self) -> PixelFormat{
+        self.format
+    }
+
     /// Allocated surface size
     #[inline]
 	pub fn used(&self) -> usize{
@@ -393,3 +1042,21 @@ impl TextureAtlas{
 	}
 
 }
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn variation_selectors(){
+        assert!(is_variation_selector('\u{FE00}'));
+        assert!(is_variation_selector('\u{FE0F}'));
+        assert!(is_variation_selector('\u{E0100}'));
+        assert!(is_variation_selector('\u{E01EF}'));
+        assert!(!is_variation_selector('\u{FDFF}'));
+        assert!(!is_variation_selector('\u{FE10}'));
+        assert!(!is_variation_selector('\u{E00FF}'));
+        assert!(!is_variation_selector('\u{E01F0}'));
+        assert!(!is_variation_selector('a'));
+    }
+}
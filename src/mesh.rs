@@ -0,0 +1,368 @@
+use batch::{VertexBatch, VertexKind};
+use shape::{Contour, Segment, Shape, Vector};
+
+/// How glyph outlines are turned into triangles.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tessellation{
+    /// Curves are flattened into lines deviating at most `tolerance` from
+    /// them, in the outline's units, and the shape filled with solid
+    /// triangles.
+    Flattened{ tolerance: f32 },
+    /// The polygon joining the curve end points is filled with solid
+    /// triangles and the curves themselves drawn with Loop-Blinn curve
+    /// triangles, which stay sharp at any scale. Cubic curves are
+    /// approximated by quadratic ones deviating at most `tolerance`.
+    CurveTriangles{ tolerance: f32 },
+}
+
+/// Upper bound on the pieces a single curve is split into.
+const MAX_STEPS: f64 = 1024.;
+
+/// Lines needed for `segment` to deviate at most `tolerance` from the curve.
+fn flatten_steps(segment: &Segment, tolerance: f64) -> usize{
+    let steps = match *segment {
+        Segment::Line(_) => 1.,
+        Segment::Quad(p) => ((p[0] - p[1] * 2. + p[2]).length() / (4. * tolerance)).sqrt(),
+        Segment::Cubic(p) => {
+            let d = (p[0] - p[1] * 2. + p[2]).length().max((p[1] - p[2] * 2. + p[3]).length());
+            (3. * d / (4. * tolerance)).sqrt()
+        }
+    };
+    steps.ceil().clamp(1., MAX_STEPS) as usize
+}
+
+/// Approximates a cubic curve with quadratic ones deviating at most
+/// `tolerance`.
+fn cubic_to_quads(segment: &Segment, tolerance: f64) -> Vec<[Vector; 3]>{
+    let p = match *segment {
+        Segment::Cubic(p) => p,
+        _ => return vec![],
+    };
+    let d = (p[3] - p[2] * 3. + p[1] * 3. - p[0]).length();
+    let steps = (d * 3f64.sqrt() / (36. * tolerance)).cbrt().ceil().clamp(1., MAX_STEPS) as usize;
+    let h = 1. / steps as f64;
+    (0 .. steps).map(|i| {
+        let (t0, t1) = (i as f64 * h, (i + 1) as f64 * h);
+        let (q0, q3) = (segment.point(t0), segment.point(t1));
+        let c1 = q0 + segment.direction(t0) * h;
+        let c2 = q3 - segment.direction(t1) * h;
+        [q0, ((c1 + c2) * 3. - q0 - q3) * 0.25, q3]
+    }).collect()
+}
+
+fn signed_area(points: &[Vector], polygon: &[usize]) -> f64{
+    let n = polygon.len();
+    (0 .. n).map(|i| points[polygon[i]].cross(points[polygon[(i + 1) % n]])).sum::<f64>() / 2.
+}
+
+/// Even-odd test of `p` against a polygon.
+fn polygon_contains(points: &[Vector], polygon: &[usize], p: Vector) -> bool{
+    let n = polygon.len();
+    let mut inside = false;
+    for i in 0 .. n {
+        let (a, b) = (points[polygon[i]], points[polygon[(i + 1) % n]]);
+        if (a.y <= p.y) != (b.y <= p.y) && a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x) > p.x {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Whether `p` is inside or on the edges of the triangle `a`, `b`, `c`.
+fn triangle_contains(a: Vector, b: Vector, c: Vector, p: Vector) -> bool{
+    let d1 = (b - a).cross(p - a);
+    let d2 = (c - b).cross(p - b);
+    let d3 = (a - c).cross(p - c);
+    let negative = d1 < 0. || d2 < 0. || d3 < 0.;
+    let positive = d1 > 0. || d2 > 0. || d3 > 0.;
+    !(negative && positive)
+}
+
+/// Connects counter clockwise `polygon` to each of its clockwise `holes`
+/// through a pair of coincident edges, giving a single polygon.
+fn merge_holes(points: &[Vector], mut polygon: Vec<usize>, mut holes: Vec<Vec<usize>>) -> Vec<usize>{
+    let max_x = |hole: &Vec<usize>| hole.iter().map(|&i| points[i].x).fold(f64::MIN, f64::max);
+    holes.sort_by(|a, b| max_x(b).total_cmp(&max_x(a)));
+    for hole in holes {
+        let m_index = (0 .. hole.len()).max_by(|&a, &b| points[hole[a]].x.total_cmp(&points[hole[b]].x)).unwrap();
+        let m = points[hole[m_index]];
+
+        // Closest edge hit by a ray going right from the hole
+        let n = polygon.len();
+        let mut hit: Option<(f64, usize)> = None;
+        for i in 0 .. n {
+            let (a, b) = (points[polygon[i]], points[polygon[(i + 1) % n]]);
+            if a.y == b.y || (a.y - m.y) * (b.y - m.y) > 0. {
+                continue
+            }
+            let x = a.x + (m.y - a.y) / (b.y - a.y) * (b.x - a.x);
+            if x >= m.x && hit.is_none_or(|(hit_x, _)| x < hit_x) {
+                hit = Some((x, i));
+            }
+        }
+        let (hit_x, edge) = match hit {
+            Some(hit) => hit,
+            None => continue,
+        };
+        let next = (edge + 1) % n;
+        let mut p_index = if points[polygon[edge]].x > points[polygon[next]].x { edge } else { next };
+
+        // A vertex inside the triangle between the hole, the hit point and
+        // the chosen end point would hide it, use the one closest in angle
+        // to the ray instead
+        let intersection = Vector::new(hit_x, m.y);
+        let p = points[polygon[p_index]];
+        if intersection != p {
+            let mut best = f64::MAX;
+            for (j, &v) in polygon.iter().enumerate() {
+                let q = points[v];
+                if q != p && q.x > m.x && triangle_contains(m, intersection, p, q) {
+                    let tangent = (q.y - m.y).abs() / (q.x - m.x);
+                    if tangent < best {
+                        best = tangent;
+                        p_index = j;
+                    }
+                }
+            }
+        }
+
+        let mut bridge = (0 ..= hole.len()).map(|k| hole[(m_index + k) % hole.len()]).collect::<Vec<_>>();
+        bridge.push(polygon[p_index]);
+        polygon.splice(p_index + 1 .. p_index + 1, bridge);
+    }
+    polygon
+}
+
+/// Triangulates the counter clockwise `polygon` by ear clipping.
+fn clip_ears(points: &[Vector], mut polygon: Vec<usize>, triangles: &mut Vec<[usize; 3]>){
+    let mut i = 0;
+    let mut stalled = 0;
+    while polygon.len() > 3 {
+        let n = polygon.len();
+        let (ia, ib, ic) = ((i + n - 1) % n, i % n, (i + 1) % n);
+        let (a, b, c) = (points[polygon[ia]], points[polygon[ib]], points[polygon[ic]]);
+        let cross = (b - a).cross(c - b);
+        if cross == 0. {
+            // no area, the vertex can go without a triangle
+            polygon.remove(ib);
+            i = ib.saturating_sub(1);
+            stalled = 0;
+            continue
+        }
+        let reflex = |j: usize| {
+            let prev = points[polygon[(j + n - 1) % n]];
+            let next = points[polygon[(j + 1) % n]];
+            (points[polygon[j]] - prev).cross(next - points[polygon[j]]) <= 0.
+        };
+        let blocked = cross < 0. || (0 .. n).any(|j| {
+            let q = points[polygon[j]];
+            j != ia && j != ib && j != ic && q != a && q != b && q != c && reflex(j) && triangle_contains(a, b, c, q)
+        });
+        // Self intersecting polygons can run out of ears, clip anyway then
+        if !blocked || stalled > n {
+            if cross > 0. {
+                triangles.push([polygon[ia], polygon[ib], polygon[ic]]);
+            }
+            polygon.remove(ib);
+            i = ib.saturating_sub(1);
+            stalled = 0;
+        }else{
+            i = (i + 1) % n;
+            stalled += 1;
+        }
+    }
+    if polygon.len() == 3 {
+        let (a, b, c) = (points[polygon[0]], points[polygon[1]], points[polygon[2]]);
+        if (b - a).cross(c - b) > 0. {
+            triangles.push([polygon[0], polygon[1], polygon[2]]);
+        }
+    }
+}
+
+/// Triangulates closed polygons, given as indices into `points`, filled with
+/// the non-zero rule. The biggest polygon's orientation is taken as the one
+/// of filled contours, polygons in the other orientation are holes in the
+/// smallest filled polygon around them.
+fn triangulate(points: &[Vector], polygons: Vec<Vec<usize>>) -> Vec<[usize; 3]>{
+    let polygons = polygons.into_iter()
+        .filter(|p| p.len() >= 3)
+        .map(|p| { let area = signed_area(points, &p); (p, area) })
+        .filter(|&(_, area)| area != 0.)
+        .collect::<Vec<_>>();
+    let fill_sign = match polygons.iter().max_by(|a, b| a.1.abs().total_cmp(&b.1.abs())) {
+        Some(&(_, area)) => area.signum(),
+        None => return vec![],
+    };
+
+    let mut outers = vec![];
+    let mut holes = vec![];
+    for (mut polygon, area) in polygons {
+        if area.signum() == fill_sign {
+            if area < 0. {
+                polygon.reverse();
+            }
+            outers.push((polygon, area.abs(), vec![]));
+        }else{
+            if area > 0. {
+                polygon.reverse();
+            }
+            holes.push(polygon);
+        }
+    }
+    for hole in holes {
+        let p = points[hole[0]];
+        let outer = outers.iter_mut()
+            .filter(|outer| polygon_contains(points, &outer.0, p))
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some(outer) = outer {
+            outer.2.push(hole);
+        }
+    }
+
+    let mut triangles = vec![];
+    for (polygon, _, holes) in outers {
+        let polygon = merge_holes(points, polygon, holes);
+        clip_ears(points, polygon, &mut triangles);
+    }
+    triangles
+}
+
+/// Whether all the points of `contour` are finite. Outlines with public
+/// fields can be built with others, their contours are skipped.
+fn is_finite(contour: &Contour) -> bool{
+    contour.iter().all(|segment| segment.points().iter().all(|p| p.x.is_finite() && p.y.is_finite()))
+}
+
+/// Sign of the signed area of filled contours, taken from the biggest one.
+fn fill_sign(shape: &Shape) -> f64{
+    shape.contours.iter()
+        .filter(|contour| is_finite(contour))
+        .map(|contour| {
+            contour.iter().flat_map(|s| s.flatten(8)).map(|(a, b)| a.cross(b)).sum::<f64>()
+        })
+        .fold(0f64, |biggest, area| if area.abs() > biggest.abs() { area } else { biggest })
+        .signum()
+}
+
+pub(crate) fn tessellate(shape: &Shape, tessellation: Tessellation) -> VertexBatch{
+    let mut batch = VertexBatch::new();
+    let mut points = vec![];
+    let mut polygons = vec![];
+    match tessellation {
+        Tessellation::Flattened{ tolerance } => {
+            let tolerance = (tolerance as f64).max(1e-6);
+            for contour in shape.contours.iter().filter(|contour| is_finite(contour)) {
+                let mut polygon = vec![];
+                for segment in contour {
+                    for (_, b) in segment.flatten(flatten_steps(segment, tolerance)) {
+                        polygon.push(points.len());
+                        points.push(b);
+                    }
+                }
+                polygons.push(polygon);
+            }
+        }
+        Tessellation::CurveTriangles{ tolerance } => {
+            let tolerance = (tolerance as f64).max(1e-6);
+            // Filled contours have the inside on their left when counter
+            // clockwise
+            let inside_left = fill_sign(shape) > 0.;
+            for contour in shape.contours.iter().filter(|contour| is_finite(contour)) {
+                let mut polygon = vec![];
+                for segment in contour {
+                    let quads = match *segment {
+                        Segment::Line(p) => {
+                            polygon.push(points.len());
+                            points.push(p[1]);
+                            continue
+                        }
+                        Segment::Quad(p) => vec![p],
+                        Segment::Cubic(_) => cubic_to_quads(segment, tolerance),
+                    };
+                    for [p0, control, p2] in quads {
+                        let side = (p2 - p0).cross(control - p0);
+                        if side != 0. {
+                            // Curves bulging into the shape keep their
+                            // control point in the polygon and are drawn
+                            // outside of the curve
+                            let concave = (side > 0.) == inside_left;
+                            let kind = if concave { VertexKind::CurveOutside } else { VertexKind::CurveInside };
+                            let corners = [(p0, [0., 0.]), (control, [0.5, 0.]), (p2, [1., 1.])];
+                            let mut indices = [0; 3];
+                            for (index, &(p, uv)) in indices.iter_mut().zip(corners.iter()) {
+                                *index = batch.push_vertex([p.x as f32, p.y as f32], uv, kind);
+                            }
+                            if side > 0. {
+                                indices.swap(1, 2);
+                            }
+                            batch.indices.extend_from_slice(&indices);
+                            if concave {
+                                polygon.push(points.len());
+                                points.push(control);
+                            }
+                        }
+                        polygon.push(points.len());
+                        points.push(p2);
+                    }
+                }
+                polygons.push(polygon);
+            }
+        }
+    }
+
+    // Points are shared between triangles but not with curve triangles
+    let base = batch.vertices.len() as u32;
+    for p in &points {
+        batch.push_vertex([p.x as f32, p.y as f32], [0., 0.], VertexKind::Solid);
+    }
+    for triangle in triangulate(&points, polygons) {
+        batch.indices.extend(triangle.iter().map(|&i| base + i as u32));
+    }
+    batch
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn square(x: f64, y: f64, size: f64) -> Contour{
+        let corners = [Vector::new(x, y), Vector::new(x + size, y), Vector::new(x + size, y + size), Vector::new(x, y + size)];
+        (0 .. 4).map(|i| Segment::Line([corners[i], corners[(i + 1) % 4]])).collect()
+    }
+
+    fn solid_triangles(batch: &VertexBatch) -> usize{
+        batch.indices.len() / 3
+    }
+
+    #[test]
+    fn square_is_two_triangles(){
+        let shape = Shape{ contours: vec![square(0., 0., 10.)] };
+        let batch = tessellate(&shape, Tessellation::Flattened{ tolerance: 0.1 });
+        assert_eq!(solid_triangles(&batch), 2);
+    }
+
+    #[test]
+    fn square_with_hole(){
+        let mut hole = square(2., 2., 6.);
+        hole.reverse();
+        for segment in &mut hole {
+            if let Segment::Line(ref mut p) = *segment {
+                p.swap(0, 1);
+            }
+        }
+        let shape = Shape{ contours: vec![square(0., 0., 10.), hole] };
+        let batch = tessellate(&shape, Tessellation::Flattened{ tolerance: 0.1 });
+        assert_eq!(solid_triangles(&batch), 8);
+    }
+
+    #[test]
+    fn non_finite_contours_are_skipped(){
+        let mut broken = square(20., 0., 10.);
+        broken[1] = Segment::Line([Vector::new(30., 0.), Vector::new(f64::NAN, 10.)]);
+        broken.push(Segment::Quad([Vector::new(20., 0.), Vector::new(f64::INFINITY, 5.), Vector::new(20., 0.)]));
+        let shape = Shape{ contours: vec![square(0., 0., 10.), broken] };
+        for &tessellation in &[Tessellation::Flattened{ tolerance: 0.1 }, Tessellation::CurveTriangles{ tolerance: 0.1 }] {
+            assert_eq!(solid_triangles(&tessellate(&shape, tessellation)), 2);
+        }
+    }
+}
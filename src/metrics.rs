@@ -0,0 +1,189 @@
+use ffi;
+use Units;
+
+/// `fsSelection` bit telling to lay out lines with the typographic metrics.
+const USE_TYPO_METRICS: u16 = 1 << 7;
+
+/// Table the ascender, descender and line gap of a font are read from.
+/// Fonts often disagree between them, and applications between which one
+/// to use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetricsSource{
+    /// The `hhea` table, what freetype and freetype-gl use. Falls back to
+    /// the typographic metrics, then the Windows ones, when it's empty.
+    Hhea,
+    /// The typographic metrics of the OS/2 table.
+    Typo,
+    /// The Windows metrics of the OS/2 table, which usually cover every
+    /// glyph of the font and have no line gap.
+    Win,
+    /// The typographic metrics if the font asks for them to be used with
+    /// the `USE_TYPO_METRICS` flag, else `Hhea`.
+    Preferred,
+}
+
+/// Ascender, descender and line gap of a font. The descender is negative
+/// below the baseline.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VerticalMetrics{
+    pub ascender: f32,
+    pub descender: f32,
+    pub line_gap: f32,
+}
+
+impl VerticalMetrics{
+    /// Distance between the baselines of consecutive lines.
+    #[inline]
+    pub fn line_height(&self) -> f32{
+        self.ascender - self.descender + self.line_gap
+    }
+
+    fn scaled(self, scale: f32) -> VerticalMetrics{
+        VerticalMetrics{
+            ascender: self.ascender * scale,
+            descender: self.descender * scale,
+            line_gap: self.line_gap * scale,
+        }
+    }
+}
+
+/// OS/2 table of `face`, `None` for fonts without one.
+unsafe fn os2<'a>(face: ffi::FT_Face) -> Option<&'a ffi::TT_OS2>{
+    let table = ffi::FT_Get_Sfnt_Table(face, ffi::FT_Sfnt_Tag__FT_SFNT_OS2) as *const ffi::TT_OS2;
+    if table.is_null() || (*table).version == 0xFFFF {
+        None
+    }else{
+        Some(&*table)
+    }
+}
+
+/// Factor from font units to `units` for a font of `size` pixels, `None`
+/// for bitmap only fonts, which have no units per em.
+pub(crate) fn units_scale(units: Units, units_per_em: u16, size: f32) -> Option<f32>{
+    match (units, units_per_em) {
+        (_, 0) => None,
+        (Units::FontUnits, _) => Some(1.),
+        (Units::Pixels, units_per_em) => Some(size / units_per_em as f32),
+    }
+}
+
+/// Vertical metrics of `face` from `source` in font units, multiplied by
+/// `scale`.
+pub(crate) unsafe fn vertical_metrics(face: ffi::FT_Face, source: MetricsSource, scale: f32) -> Option<VerticalMetrics>{
+    let hhea = if (*face).face_flags & ffi::FT_FACE_FLAG_SCALABLE as ffi::FT_Long == 0 {
+        None
+    }else{
+        let face = &*face;
+        Some(VerticalMetrics{
+            ascender: face.ascender as f32,
+            descender: face.descender as f32,
+            line_gap: face.height as f32 - face.ascender as f32 + face.descender as f32,
+        })
+    };
+    select(source, os2(face), hhea).map(|metrics| metrics.scaled(scale))
+}
+
+/// Metrics from `source` given the OS/2 table and the `hhea` metrics.
+fn select(source: MetricsSource, os2: Option<&ffi::TT_OS2>, hhea: Option<VerticalMetrics>) -> Option<VerticalMetrics>{
+    let source = match source {
+        MetricsSource::Preferred => match os2 {
+            Some(os2) if os2.fsSelection & USE_TYPO_METRICS != 0 => MetricsSource::Typo,
+            _ => MetricsSource::Hhea,
+        },
+        source => source,
+    };
+    let metrics = match source {
+        MetricsSource::Typo => {
+            let os2 = os2?;
+            VerticalMetrics{
+                ascender: os2.sTypoAscender as f32,
+                descender: os2.sTypoDescender as f32,
+                line_gap: os2.sTypoLineGap as f32,
+            }
+        }
+        MetricsSource::Win => {
+            let os2 = os2?;
+            VerticalMetrics{
+                ascender: os2.usWinAscent as f32,
+                descender: -(os2.usWinDescent as f32),
+                line_gap: 0.,
+            }
+        }
+        _ => return hhea,
+    };
+    Some(metrics)
+}
+
+/// x-height of `face` in font units, from version 2 and up OS/2 tables.
+pub(crate) unsafe fn x_height(face: ffi::FT_Face) -> Option<f32>{
+    match os2(face) {
+        Some(os2) if os2.version >= 2 && os2.sxHeight > 0 => Some(os2.sxHeight as f32),
+        _ => None,
+    }
+}
+
+/// Cap height of `face` in font units, from version 2 and up OS/2 tables.
+pub(crate) unsafe fn cap_height(face: ffi::FT_Face) -> Option<f32>{
+    match os2(face) {
+        Some(os2) if os2.version >= 2 && os2.sCapHeight > 0 => Some(os2.sCapHeight as f32),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use std::mem;
+
+    use super::*;
+
+    fn os2(fs_selection: u16) -> ffi::TT_OS2{
+        let mut os2 = unsafe{ mem::zeroed::<ffi::TT_OS2>() };
+        os2.version = 4;
+        os2.fsSelection = fs_selection;
+        os2.sTypoAscender = 750;
+        os2.sTypoDescender = -250;
+        os2.sTypoLineGap = 200;
+        os2.usWinAscent = 900;
+        os2.usWinDescent = 300;
+        os2
+    }
+
+    const HHEA: VerticalMetrics = VerticalMetrics{ ascender: 800., descender: -200., line_gap: 100. };
+
+    #[test]
+    fn sources(){
+        let os2 = os2(0);
+        let typo = VerticalMetrics{ ascender: 750., descender: -250., line_gap: 200. };
+        let win = VerticalMetrics{ ascender: 900., descender: -300., line_gap: 0. };
+        assert_eq!(select(MetricsSource::Hhea, Some(&os2), Some(HHEA)), Some(HHEA));
+        assert_eq!(select(MetricsSource::Typo, Some(&os2), Some(HHEA)), Some(typo));
+        assert_eq!(select(MetricsSource::Win, Some(&os2), Some(HHEA)), Some(win));
+        assert_eq!(typo.line_height(), 1200.);
+        assert_eq!(win.line_height(), 1200.);
+    }
+
+    #[test]
+    fn preferred_source(){
+        let typo = select(MetricsSource::Typo, Some(&os2(0)), None);
+        assert_eq!(select(MetricsSource::Preferred, Some(&os2(USE_TYPO_METRICS)), Some(HHEA)), typo);
+        assert_eq!(select(MetricsSource::Preferred, Some(&os2(0)), Some(HHEA)), Some(HHEA));
+        assert_eq!(select(MetricsSource::Preferred, None, Some(HHEA)), Some(HHEA));
+    }
+
+    #[test]
+    fn missing_tables(){
+        assert_eq!(select(MetricsSource::Typo, None, Some(HHEA)), None);
+        assert_eq!(select(MetricsSource::Win, None, Some(HHEA)), None);
+        assert_eq!(select(MetricsSource::Hhea, Some(&os2(0)), None), None);
+    }
+
+    #[test]
+    fn design_unit_scaling(){
+        assert_eq!(units_scale(Units::FontUnits, 1000, 16.), Some(1.));
+        assert_eq!(units_scale(Units::Pixels, 1000, 16.), Some(0.016));
+        assert_eq!(units_scale(Units::Pixels, 2048, 32.), Some(1. / 64.));
+        assert_eq!(units_scale(Units::Pixels, 0, 16.), None);
+        assert_eq!(units_scale(Units::FontUnits, 0, 16.), None);
+        assert_eq!(HHEA.scaled(0.5), VerticalMetrics{ ascender: 400., descender: -100., line_gap: 50. });
+    }
+}
@@ -0,0 +1,238 @@
+use sdf::{self, DistanceField, SdfOptions};
+use shape::{Segment, Shape, SignedDistance, Vector};
+
+const BLACK: u8 = 0;
+const RED: u8 = 1;
+const GREEN: u8 = 2;
+const BLUE: u8 = 4;
+const YELLOW: u8 = RED | GREEN;
+const MAGENTA: u8 = RED | BLUE;
+const CYAN: u8 = GREEN | BLUE;
+const WHITE: u8 = RED | GREEN | BLUE;
+
+/// Minimum angle in radians between two segments for their junction to be a
+/// corner that keeps its sharpness.
+const CORNER_ANGLE: f64 = 3.;
+
+/// Minimum difference between neighbouring pixels, in pixels, for them to be
+/// considered clashing during error correction.
+const CLASH_THRESHOLD: f64 = 1.001;
+
+/// Picks the next color for a run of edges. `banned` is a color the next
+/// one shouldn't share a single channel with.
+fn switch_color(color: &mut u8, seed: &mut u64, banned: u8){
+    let combined = *color & banned;
+    if combined == RED || combined == GREEN || combined == BLUE {
+        *color = combined ^ WHITE;
+        return
+    }
+    if *color == BLACK || *color == WHITE {
+        *color = [CYAN, MAGENTA, YELLOW][(*seed % 3) as usize];
+        *seed /= 3;
+        return
+    }
+    let shifted = (*color as u32) << (1 + (*seed & 1));
+    *color = ((shifted | shifted >> 3) & WHITE as u32) as u8;
+    *seed >>= 1;
+}
+
+fn is_corner(a: Vector, b: Vector, cross_threshold: f64) -> bool{
+    a.dot(b) <= 0. || a.cross(b).abs() > cross_threshold
+}
+
+/// Maps `position` out of `n` to -1, 0 or 1, symmetrically.
+fn symmetrical_trichotomy(position: usize, n: usize) -> usize{
+    (3. + 2.875 * position as f64 / (n - 1) as f64 - 1.4375 + 0.5) as usize - 2
+}
+
+/// Segment of a contour with the channels it contributes its distance to.
+struct Edge{
+    segment: Segment,
+    color: u8,
+}
+
+/// Assigns colors to the edges of every contour so that the two edges
+/// meeting at each corner share only one channel, which keeps the corner
+/// sharp once the median of the channels is taken.
+fn color_edges(shape: &Shape) -> Vec<Vec<Edge>>{
+    let cross_threshold = CORNER_ANGLE.sin();
+    let mut seed = 0u64;
+    let mut contours = vec![];
+    for contour in &shape.contours {
+        let mut corners = vec![];
+        let mut prev_direction = contour[contour.len() - 1].direction(1.);
+        for (i, segment) in contour.iter().enumerate() {
+            if is_corner(prev_direction.normalize(), segment.direction(0.).normalize(), cross_threshold) {
+                corners.push(i);
+            }
+            prev_direction = segment.direction(1.);
+        }
+
+        let m = contour.len();
+        let mut edges = contour.iter().map(|&segment| Edge{ segment, color: WHITE }).collect::<Vec<_>>();
+        if corners.len() == 1 {
+            // Teardrop, split the contour in three runs of edges around the
+            // only corner
+            let mut colors = [WHITE; 3];
+            switch_color(&mut colors[0], &mut seed, BLACK);
+            colors[2] = colors[0];
+            switch_color(&mut colors[2], &mut seed, BLACK);
+            let corner = corners[0];
+            if m >= 3 {
+                for i in 0 .. m {
+                    edges[(corner + i) % m].color = colors[symmetrical_trichotomy(i, m)];
+                }
+            }else{
+                // Too few edges to color, split them
+                let mut parts = vec![];
+                for segment in contour {
+                    parts.extend_from_slice(&segment.split_in_thirds());
+                }
+                if m == 2 && corner == 1 {
+                    parts.rotate_left(3);
+                }
+                let part_colors: &[u8] = if m == 1 {
+                    &colors
+                }else{
+                    &[colors[0], colors[0], colors[1], colors[1], colors[2], colors[2]]
+                };
+                edges = parts.into_iter().zip(part_colors).map(|(segment, &color)| Edge{ segment, color }).collect();
+            }
+        }else if corners.len() > 1 {
+            let mut spline = 0;
+            let start = corners[0];
+            let mut color = WHITE;
+            switch_color(&mut color, &mut seed, BLACK);
+            let initial_color = color;
+            for i in 0 .. m {
+                let index = (start + i) % m;
+                if spline + 1 < corners.len() && corners[spline + 1] == index {
+                    spline += 1;
+                    let banned = if spline == corners.len() - 1 { initial_color } else { BLACK };
+                    switch_color(&mut color, &mut seed, banned);
+                }
+                edges[index].color = color;
+            }
+        }
+        contours.push(edges);
+    }
+    contours
+}
+
+/// Closest edge found so far for one channel.
+#[derive(Clone, Copy)]
+struct Closest<'a>{
+    distance: SignedDistance,
+    edge: Option<&'a Segment>,
+    param: f64,
+}
+
+/// Pseudo distances to the closest edge of each channel at `p`.
+fn multi_distance(contours: &[Vec<Edge>], p: Vector) -> [f64; 3]{
+    let mut closest = [Closest{ distance: SignedDistance::INFINITE, edge: None, param: 0. }; 3];
+    for edge in contours.iter().flat_map(|c| c.iter()) {
+        let (distance, param) = edge.segment.signed_distance(p);
+        for (channel, closest) in closest.iter_mut().enumerate() {
+            if edge.color & (1 << channel) != 0 && distance.closer_than(&closest.distance) {
+                *closest = Closest{ distance, edge: Some(&edge.segment), param };
+            }
+        }
+    }
+    let mut distances = [0.; 3];
+    for (distance, closest) in distances.iter_mut().zip(closest.iter_mut()) {
+        if let Some(edge) = closest.edge {
+            edge.distance_to_pseudo_distance(&mut closest.distance, p, closest.param);
+        }
+        *distance = closest.distance.distance;
+    }
+    distances
+}
+
+#[inline]
+fn median(a: f64, b: f64, c: f64) -> f64{
+    a.min(b).max(a.max(b).min(c))
+}
+
+/// Whether the pixels `a` and `b` would interpolate to a wrong median. Only
+/// the one further from the edge is reported.
+fn clashes(a: &[f64], b: &[f64], threshold: f64) -> bool{
+    let (mut a0, mut a1, mut a2) = (a[0], a[1], a[2]);
+    let (mut b0, mut b1, mut b2) = (b[0], b[1], b[2]);
+    // Sort the channels from biggest to smallest difference
+    if (b1 - a1).abs() < (b2 - a2).abs() {
+        ::std::mem::swap(&mut a1, &mut a2);
+        ::std::mem::swap(&mut b1, &mut b2);
+    }
+    if (b0 - a0).abs() < (b1 - a1).abs() {
+        ::std::mem::swap(&mut a0, &mut a1);
+        ::std::mem::swap(&mut b0, &mut b1);
+        if (b1 - a1).abs() < (b2 - a2).abs() {
+            ::std::mem::swap(&mut a1, &mut a2);
+            ::std::mem::swap(&mut b1, &mut b2);
+        }
+    }
+    (b1 - a1).abs() >= threshold && (a2 - 0.5).abs() >= (b2 - 0.5).abs()
+}
+
+/// Replaces pixels that clash with a neighbour by their median, losing the
+/// corner there but avoiding artifacts.
+fn correct_errors(data: &mut [f64], width: usize, height: usize, threshold: f64){
+    let mut clashing = vec![];
+    for y in 0 .. height {
+        for x in 0 .. width {
+            let i = (y * width + x) * 3;
+            let pixel = &data[i .. i + 3];
+            let neighbour = |x: usize, y: usize| &data[(y * width + x) * 3 .. (y * width + x) * 3 + 3];
+            if (x > 0 && clashes(pixel, neighbour(x - 1, y), threshold))
+                || (x + 1 < width && clashes(pixel, neighbour(x + 1, y), threshold))
+                || (y > 0 && clashes(pixel, neighbour(x, y - 1), threshold))
+                || (y + 1 < height && clashes(pixel, neighbour(x, y + 1), threshold))
+            {
+                clashing.push(i);
+            }
+        }
+    }
+    for i in clashing {
+        let m = median(data[i], data[i + 1], data[i + 2]);
+        data[i] = m;
+        data[i + 1] = m;
+        data[i + 2] = m;
+    }
+}
+
+/// Computes the multi-channel distance field of `shape`, in pixels with y
+/// pointing up. The median of the three channels follows the same mapping as
+/// single channel fields. Upsampling doesn't apply, distances are measured
+/// to the outline itself.
+pub(crate) fn distance_field(shape: &Shape, options: &SdfOptions) -> DistanceField{
+    let (left, top, width, height) = sdf::outline_grid(shape, options.padding());
+
+    let contours = color_edges(shape);
+    let range = 2. * options.spread.max(1.) as f64;
+    let mut data = Vec::with_capacity(width * height * 3);
+    for y in 0 .. height {
+        for x in 0 .. width {
+            let p = Vector::new(left as f64 + x as f64 + 0.5, top as f64 - y as f64 - 0.5);
+            let mut distances = multi_distance(&contours, p);
+            // The sign depends on the contours orientation, fix it with the
+            // fill rule so overlapping and reversed contours work too
+            let inside = shape.contains(p);
+            if (median(distances[0], distances[1], distances[2]) > 0.) != inside {
+                for d in distances.iter_mut() {
+                    *d = -*d;
+                }
+            }
+            data.extend(distances.iter().map(|d| 0.5 + d / range));
+        }
+    }
+    correct_errors(&mut data, width, height, CLASH_THRESHOLD / range);
+
+    DistanceField{
+        data: data.into_iter().map(|v| v as f32).collect(),
+        channels: 3,
+        width,
+        height,
+        left,
+        top,
+    }
+}
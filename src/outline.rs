@@ -0,0 +1,137 @@
+use batch::VertexBatch;
+use mesh::{self, Tessellation};
+use shape::{Segment, Shape, Vector};
+
+/// Units of glyph outline coordinates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Units{
+    /// Unscaled and unhinted design units, see `units_per_EM` in the face.
+    FontUnits,
+    /// Pixels at the font's size, hinted like rendered glyphs.
+    Pixels,
+}
+
+/// Drawing command of a glyph outline, with y pointing up from the baseline.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathCommand{
+    /// Starts a new contour at a point.
+    MoveTo([f32; 2]),
+    /// Straight line to a point.
+    LineTo([f32; 2]),
+    /// Quadratic bezier curve through a control point to a point.
+    QuadTo([f32; 2], [f32; 2]),
+    /// Cubic bezier curve through two control points to a point.
+    CubicTo([f32; 2], [f32; 2], [f32; 2]),
+    /// Closes the contour back to its starting point.
+    Close,
+}
+
+/// Direction a contour turns in, with y pointing up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Winding{
+    Clockwise,
+    CounterClockwise,
+}
+
+/// Closed contour of a glyph outline.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Contour{
+    /// Starts with `MoveTo` and ends with `Close`.
+    pub commands: Vec<PathCommand>,
+    pub winding: Winding,
+}
+
+/// Bounding box of all the points of an outline, including control points.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ControlBox{
+    pub x_min: f32,
+    pub y_min: f32,
+    pub x_max: f32,
+    pub y_max: f32,
+}
+
+/// Vector shape of a glyph.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Outline{
+    pub contours: Vec<Contour>,
+    /// All zeros for empty glyphs.
+    pub control_box: ControlBox,
+}
+
+impl Outline{
+    pub(crate) fn from_shape(shape: &Shape) -> Outline{
+        let point = |p: Vector| [p.x as f32, p.y as f32];
+        let contours = shape.contours.iter().map(|contour| {
+            let mut commands = vec![PathCommand::MoveTo(point(contour[0].start()))];
+            let mut area = 0.;
+            for segment in contour {
+                commands.push(match *segment {
+                    Segment::Line(p) => PathCommand::LineTo(point(p[1])),
+                    Segment::Quad(p) => PathCommand::QuadTo(point(p[1]), point(p[2])),
+                    Segment::Cubic(p) => PathCommand::CubicTo(point(p[1]), point(p[2]), point(p[3])),
+                });
+                for (a, b) in segment.flatten(16) {
+                    area += a.cross(b);
+                }
+            }
+            commands.push(PathCommand::Close);
+            Contour{
+                commands,
+                winding: if area > 0. { Winding::CounterClockwise } else { Winding::Clockwise },
+            }
+        }).collect();
+
+        let control_box = if shape.is_empty() {
+            ControlBox::default()
+        }else{
+            let (x_min, y_min, x_max, y_max) = shape.control_box();
+            ControlBox{ x_min: x_min as f32, y_min: y_min as f32, x_max: x_max as f32, y_max: y_max as f32 }
+        };
+
+        Outline{
+            contours,
+            control_box,
+        }
+    }
+
+    pub(crate) fn to_shape(&self) -> Shape{
+        let point = |p: [f32; 2]| Vector::new(p[0] as f64, p[1] as f64);
+        let mut shape = Shape::default();
+        for contour in &self.contours {
+            let mut segments = vec![];
+            let mut start = Vector::default();
+            let mut last = start;
+            for command in &contour.commands {
+                let segment = match *command {
+                    PathCommand::MoveTo(p) => {
+                        start = point(p);
+                        last = start;
+                        continue
+                    }
+                    PathCommand::LineTo(p) => Segment::Line([last, point(p)]),
+                    PathCommand::QuadTo(c, p) => Segment::Quad([last, point(c), point(p)]),
+                    PathCommand::CubicTo(c1, c2, p) => Segment::Cubic([last, point(c1), point(c2), point(p)]),
+                    PathCommand::Close if last != start => Segment::Line([last, start]),
+                    PathCommand::Close => continue,
+                };
+                last = segment.end();
+                segments.push(segment);
+            }
+            if !segments.is_empty() {
+                shape.contours.push(segments);
+            }
+        }
+        shape
+    }
+
+    /// Commands of all the contours in order.
+    pub fn commands<'a>(&'a self) -> impl Iterator<Item = PathCommand> + 'a{
+        self.contours.iter().flat_map(|contour| contour.commands.iter().cloned())
+    }
+
+    /// Triangles filling the outline, in its units. Overlapping contours
+    /// give overlapping triangles.
+    pub fn tessellate(&self, tessellation: Tessellation) -> VertexBatch{
+        mesh::tessellate(&self.to_shape(), tessellation)
+    }
+}
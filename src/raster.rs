@@ -0,0 +1,850 @@
+use std::ptr;
+use std::slice;
+
+use ffi;
+use msdf;
+use sdf::{self, DistanceField, FieldSource, SdfOptions};
+use shape::Shape;
+#[cfg(feature = "color")]
+use color;
+use stroke::{self, StrokeOptions};
+use {Hinting, LcdFilter, PixelFormat, TextureGlyph};
+
+/// Equivalent to freetype's FT_LOAD_TARGET_XXX macros.
+#[inline]
+pub(crate) fn load_target(mode: ffi::FT_Render_Mode) -> ffi::FT_Int32{
+    ((mode & 15) << 16) as ffi::FT_Int32
+}
+
+/// Glyph options, most of which freetype-gl doesn't support. When any of
+/// those is set glyphs are rasterized from rust instead of through
+/// freetype-gl.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct RasterOptions{
+    /// Format of the atlas glyphs are packed into.
+    pub format: PixelFormat,
+    pub hinting: Hinting,
+    /// Only used for LCD rendering, to `RGB8` atlases.
+    pub lcd_filter: LcdFilter,
+    /// Outline emboldening strength in 26.6 fractional pixels.
+    pub embolden: ffi::FT_Pos,
+    /// Horizontal shear applied to the outline, 0 for upright glyphs.
+    pub oblique: f32,
+    /// Number of horizontal subpixel positions each glyph is rasterized at,
+    /// 0 to only rasterize glyphs at whole pixels.
+    pub subpixel_phases: u8,
+    /// Distance fields are generated from rust when set.
+    pub sdf: Option<SdfOptions>,
+    /// What distance fields are measured from.
+    pub field_source: FieldSource,
+    /// Used by the outline render modes.
+    pub stroke: StrokeOptions,
+    /// Outline render modes also write the fill coverage, to the first
+    /// channel with the outline in the second.
+    pub fill_and_outline: bool,
+    /// Set for fonts without outlines, which are loaded from a bitmap strike.
+    pub strike: Option<Strike>,
+    /// CPAL palette layered color glyphs are drawn with, in `RGBA8` atlases.
+    #[cfg(feature = "color")]
+    pub palette: u16,
+    /// Straight alpha RGBA color of the layers drawn in the text color, in
+    /// layered color glyphs that also have palette colors.
+    #[cfg(feature = "color")]
+    pub foreground: [f32; 4],
+    /// Whether the font has design axes, glyphs are rasterized from rust so
+    /// they can be cached per design coordinates.
+    pub variable: bool,
+}
+
+impl RasterOptions{
+    /// Whether glyphs can be left to freetype-gl's own rasterizer.
+    #[inline]
+    pub fn freetype_gl_compatible(&self) -> bool{
+        let hinting = match self.hinting {
+            Hinting::None | Hinting::AutoHint => true,
+            Hinting::Light | Hinting::Normal => false,
+        };
+        // freetype-gl can't disable filtering or use the legacy filter
+        let lcd_filter = match self.lcd_filter {
+            LcdFilter::None | LcdFilter::Legacy => false,
+            LcdFilter::Default | LcdFilter::FreeType | LcdFilter::Light | LcdFilter::Custom(_) => true,
+        };
+        // freetype-gl only writes coverage, either 8 bit or per subpixel
+        let format = match self.format {
+            PixelFormat::R8 | PixelFormat::RGB8 => true,
+            PixelFormat::RGBA8 | PixelFormat::R32F => false,
+        };
+        format && hinting && lcd_filter && self.embolden == 0 && self.oblique == 0. && self.subpixel_phases == 0
+            && self.sdf.is_none() && self.stroke.freetype_gl_compatible() && self.strike.is_none()
+            && !self.variable
+    }
+
+    /// Rounds the pen position `x` to the nearest subpixel phase, returning
+    /// the whole pixel position the glyph has to be drawn at and the phase.
+    pub fn snap(&self, x: f32) -> (f32, u8){
+        if self.subpixel_phases < 2 {
+            return (x, 0)
+        }
+        let phases = self.subpixel_phases as f32;
+        let quantized = (x * phases).round();
+        let pixel = (quantized / phases).floor();
+        (pixel, (quantized - pixel * phases) as u8)
+    }
+}
+
+/// Fixed size bitmap strike of a font without outlines.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Strike{
+    /// Index in the face's available sizes.
+    pub index: i32,
+    /// Size of the strike in pixels.
+    pub size: f32,
+    /// Ratio between the font's size and the strike's.
+    pub scale: f32,
+    /// Whether the strike holds color bitmaps.
+    pub color: bool,
+}
+
+/// Fixed size bitmap strike of a font, as listed by
+/// `TextureFontBuilder::fixed_sizes`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FixedSize{
+    /// Index to pass to `TextureFontBuilder::fixed_size`.
+    pub index: usize,
+    /// Size in pixels, the font's size when the strike is selected.
+    pub size: f32,
+    /// Horizontal size in pixels, which differs from `size` for strikes
+    /// with non square pixels.
+    pub width: f32,
+    /// Height in pixels of the font's glyph cells, eg. the line height of
+    /// terminal fonts.
+    pub cell_height: u32,
+    /// Average width in pixels of the font's glyph cells.
+    pub cell_width: u32,
+}
+
+/// Fixed size bitmap strikes of `face`, empty for most fonts with outlines.
+pub(crate) unsafe fn fixed_sizes(face: ffi::FT_Face) -> Vec<FixedSize>{
+    if (*face).num_fixed_sizes <= 0 || (*face).available_sizes.is_null() {
+        return vec![]
+    }
+    let sizes = slice::from_raw_parts((*face).available_sizes, (*face).num_fixed_sizes as usize);
+    sizes.iter().enumerate().map(|(index, size)| {
+        FixedSize{
+            index,
+            size: size.y_ppem as f32 / 64.,
+            width: size.x_ppem as f32 / 64.,
+            cell_height: size.height.max(0) as u32,
+            cell_width: size.width.max(0) as u32,
+        }
+    }).collect()
+}
+
+/// Strike of `face` to load bitmaps from for a font of `pixel_size`, the
+/// smallest one at least as big, or the biggest one. None for faces with
+/// outlines.
+pub(crate) unsafe fn nearest_strike(face: ffi::FT_Face, pixel_size: f32) -> Option<Strike>{
+    if (*face).face_flags & ffi::FT_FACE_FLAG_SCALABLE as ffi::FT_Long != 0 {
+        return None
+    }
+    let sizes = fixed_sizes(face);
+    let index = sizes.iter()
+        .filter(|s| s.size >= pixel_size)
+        .min_by(|a, b| a.size.partial_cmp(&b.size).unwrap())
+        .or_else(|| sizes.iter().max_by(|a, b| a.size.partial_cmp(&b.size).unwrap()))?
+        .index;
+    strike(face, index, pixel_size)
+}
+
+/// Strike `index` of `face` for a font of `pixel_size`, None if the face has
+/// no such strike.
+pub(crate) unsafe fn strike(face: ffi::FT_Face, index: usize, pixel_size: f32) -> Option<Strike>{
+    let size = fixed_sizes(face).get(index)?.size;
+    Some(Strike{
+        index: index as i32,
+        size,
+        scale: pixel_size / size,
+        color: (*face).face_flags & ffi::FT_FACE_FLAG_COLOR as ffi::FT_Long != 0,
+    })
+}
+
+/// Identifies a glyph rasterized from rust.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct GlyphKey{
+    pub glyph_id: u32,
+    /// Horizontal subpixel phase, see `RasterOptions::subpixel_phases`.
+    pub phase: u8,
+    /// Design coordinates of variable fonts, as an index in the font's
+    /// instances.
+    pub instance: u16,
+}
+
+/// Empty border around a glyph in the atlas, in pixels.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Padding{
+    pub left: usize,
+    pub top: usize,
+    pub right: usize,
+    pub bottom: usize,
+}
+
+/// Padding freetype-gl leaves around regular glyphs.
+pub(crate) const DEFAULT_PADDING: Padding = Padding{ left: 0, top: 0, right: 1, bottom: 1 };
+
+/// A rendered glyph image ready to be packed in the atlas.
+pub(crate) struct Bitmap<'a>{
+    pub data: &'a [u8],
+    /// Width in pixels.
+    pub width: usize,
+    /// Height in pixels.
+    pub height: usize,
+    /// Bytes between the start of two consecutive rows.
+    pub pitch: usize,
+    /// Left bearing in pixels.
+    pub left: i32,
+    /// Top bearing in pixels.
+    pub top: i32,
+}
+
+impl<'a> Bitmap<'a>{
+    /// Bitmap rendered in a glyph slot. `depth` is the number of bytes per
+    /// pixel of the rendered data.
+    pub unsafe fn from_slot(slot: ffi::FT_GlyphSlot, depth: usize) -> Bitmap<'a>{
+        Bitmap::from_ft_bitmap(&(*slot).bitmap, (*slot).bitmap_left, (*slot).bitmap_top, depth)
+    }
+
+    /// Bitmap rendered by freetype with the given bearings.
+    pub unsafe fn from_ft_bitmap(bitmap: &'a ffi::FT_Bitmap, left: i32, top: i32, depth: usize) -> Bitmap<'a>{
+        let pitch = bitmap.pitch.unsigned_abs() as usize;
+        let data = if bitmap.buffer.is_null() {
+            &[][..]
+        }else{
+            slice::from_raw_parts(bitmap.buffer, pitch * bitmap.rows as usize)
+        };
+        Bitmap{
+            data,
+            width: bitmap.width as usize / depth,
+            height: bitmap.rows as usize,
+            pitch,
+            left,
+            top,
+        }
+    }
+}
+
+/// Copies `bitmap` into a new region of the font's atlas, surrounded by
+/// `padding`, and creates a glyph pointing to it. The glyph's advance is left
+/// for the caller to fill.
+pub(crate) unsafe fn pack_glyph(
+    font: *mut ffi::texture_font_t,
+    codepoint: u32,
+    glyph_id: u32,
+    bitmap: &Bitmap,
+    padding: Padding) -> *mut ffi::texture_glyph_t
+{
+    let atlas = (*font).atlas;
+    let depth = (*atlas).depth as usize;
+    let width = bitmap.width + padding.left + padding.right;
+    let height = bitmap.height + padding.top + padding.bottom;
+
+    let mut buffer = vec![0u8; width * height * depth];
+    for row in 0 .. bitmap.height {
+        let src = &bitmap.data[row * bitmap.pitch .. row * bitmap.pitch + bitmap.width * depth];
+        let dst = ((row + padding.top) * width + padding.left) * depth;
+        buffer[dst .. dst + src.len()].copy_from_slice(src);
+    }
+
+    let region = ffi::texture_atlas_get_region(atlas, width as ffi::size_t, height as ffi::size_t);
+    let (x, y) = (region.data[0], region.data[1]);
+    if x < 0 {
+        return ptr::null_mut()
+    }
+    ffi::texture_atlas_set_region(
+        atlas,
+        x as ffi::size_t,
+        y as ffi::size_t,
+        width as ffi::size_t,
+        height as ffi::size_t,
+        buffer.as_ptr(),
+        (width * depth) as ffi::size_t);
+
+    let atlas_width = (*atlas).width as f32;
+    let atlas_height = (*atlas).height as f32;
+    let glyph = ffi::texture_glyph_new();
+    if glyph.is_null() {
+        return glyph
+    }
+    (*glyph).codepoint = codepoint;
+    (*glyph).glyph_id = glyph_id;
+    (*glyph).width = width as ffi::size_t;
+    (*glyph).height = height as ffi::size_t;
+    (*glyph).rendermode = (*font).rendermode;
+    (*glyph).outline_thickness = (*font).outline_thickness;
+    (*glyph).offset_x = bitmap.left - padding.left as i32;
+    (*glyph).offset_y = bitmap.top + padding.top as i32;
+    (*glyph).s0 = x as f32 / atlas_width;
+    (*glyph).t0 = y as f32 / atlas_height;
+    (*glyph).s1 = (x as usize + width) as f32 / atlas_width;
+    (*glyph).t1 = (y as usize + height) as f32 / atlas_height;
+    glyph
+}
+
+/// Stores the kerning of `glyph` with itself and after each of `lefts` in
+/// `glyph`'s kerning vector the same way freetype-gl does, and the kerning of
+/// `glyph` before each of `rights` in theirs.
+pub(crate) unsafe fn generate_kerning(
+    face: ffi::FT_Face,
+    glyph: *mut ffi::texture_glyph_t,
+    lefts: &[*mut ffi::texture_glyph_t],
+    rights: &[*mut ffi::texture_glyph_t])
+{
+    let kerning = |left: *mut ffi::texture_glyph_t, right: *mut ffi::texture_glyph_t| {
+        let mut kerning = ffi::FT_Vector{ x: 0, y: 0 };
+        ffi::FT_Get_Kerning(
+            face,
+            (*left).glyph_id,
+            (*right).glyph_id,
+            ffi::FT_Kerning_Mode__FT_KERNING_UNFITTED,
+            &mut kerning);
+        if kerning.x != 0 {
+            let k = ffi::kerning_t{ codepoint: (*left).codepoint, kerning: kerning.x as f32 / 64. };
+            ffi::vector_push_back((*right).kerning, &k as *const ffi::kerning_t as *const _);
+        }
+    };
+    kerning(glyph, glyph);
+    for &left in lefts {
+        kerning(left, glyph);
+    }
+    for &right in rights {
+        kerning(glyph, right);
+    }
+}
+
+/// Expands 8 bit coverage to premultiplied white RGBA8.
+fn coverage_to_rgba(bitmap: &Bitmap) -> Vec<u8>{
+    let mut rgba = Vec::with_capacity(bitmap.width * bitmap.height * 4);
+    for row in 0 .. bitmap.height {
+        for &c in &bitmap.data[row * bitmap.pitch .. row * bitmap.pitch + bitmap.width] {
+            rgba.extend_from_slice(&[c, c, c, c]);
+        }
+    }
+    rgba
+}
+
+/// Left and top bearings, width and height of the union of the bounds of the
+/// non empty `bitmaps`.
+pub(crate) fn union_bounds(bitmaps: &[&Bitmap]) -> Option<(i32, i32, usize, usize)>{
+    let bitmaps = bitmaps.iter().filter(|b| b.width > 0 && b.height > 0).collect::<Vec<_>>();
+    if bitmaps.is_empty() {
+        return None
+    }
+    let left = bitmaps.iter().map(|b| b.left).min().unwrap();
+    let top = bitmaps.iter().map(|b| b.top).max().unwrap();
+    let right = bitmaps.iter().map(|b| b.left + b.width as i32).max().unwrap();
+    let bottom = bitmaps.iter().map(|b| b.top - b.height as i32).min().unwrap();
+    Some((left, top, (right - left) as usize, (top - bottom) as usize))
+}
+
+/// Merges the 8 bit coverage of `layers` into one channel each of `depth`
+/// bytes per pixel, over the union of their bounds. The fourth channel gets
+/// the highest coverage of all the layers.
+fn layer_coverage<'a>(layers: &[&Bitmap], depth: usize, data: &'a mut Vec<u8>) -> Bitmap<'a>{
+    let (left, top, width, height) = match union_bounds(layers) {
+        Some(bounds) => bounds,
+        None => return Bitmap{ data: &[], width: 0, height: 0, pitch: 0, left: 0, top: 0 },
+    };
+
+    data.clear();
+    data.resize(width * height * depth, 0);
+    for (channel, bitmap) in layers.iter().enumerate() {
+        let x0 = (bitmap.left - left) as usize;
+        let y0 = (top - bitmap.top) as usize;
+        for row in 0 .. bitmap.height {
+            for (col, &c) in bitmap.data[row * bitmap.pitch .. row * bitmap.pitch + bitmap.width].iter().enumerate() {
+                let i = ((y0 + row) * width + x0 + col) * depth;
+                data[i + channel] = c;
+                if depth == 4 {
+                    data[i + 3] = data[i + 3].max(c);
+                }
+            }
+        }
+    }
+    Bitmap{
+        data,
+        width,
+        height,
+        pitch: width * depth,
+        left,
+        top,
+    }
+}
+
+/// Converts a bitmap loaded from a strike to tightly packed pixels of
+/// `format`. Color bitmaps become premultiplied RGBA, monochrome ones 0 or
+/// 255 coverage. None for pixel modes the format can't hold.
+unsafe fn strike_pixels(bitmap: &ffi::FT_Bitmap, format: PixelFormat) -> Option<Vec<u8>>{
+    let width = bitmap.width as usize;
+    let pitch = bitmap.pitch.unsigned_abs() as usize;
+    let depth = format.depth();
+    if width == 0 || bitmap.rows == 0 {
+        return Some(vec![])
+    }
+    let data = slice::from_raw_parts(bitmap.buffer, pitch * bitmap.rows as usize);
+    let rows = data.chunks(pitch);
+    let mut pixels = Vec::with_capacity(width * bitmap.rows as usize * depth);
+    match (bitmap.pixel_mode as ffi::FT_Pixel_Mode, format) {
+        (ffi::FT_Pixel_Mode__FT_PIXEL_MODE_BGRA, PixelFormat::RGBA8) => {
+            // freetype's color bitmaps are already premultiplied
+            for row in rows {
+                for bgra in row[.. width * 4].chunks(4) {
+                    pixels.extend_from_slice(&[bgra[2], bgra[1], bgra[0], bgra[3]]);
+                }
+            }
+        }
+        (ffi::FT_Pixel_Mode__FT_PIXEL_MODE_GRAY, PixelFormat::R8)
+            | (ffi::FT_Pixel_Mode__FT_PIXEL_MODE_GRAY, PixelFormat::RGB8)
+            | (ffi::FT_Pixel_Mode__FT_PIXEL_MODE_GRAY, PixelFormat::RGBA8) =>
+        {
+            for row in rows {
+                for &c in &row[.. width] {
+                    pixels.extend(::std::iter::repeat_n(c, depth));
+                }
+            }
+        }
+        (ffi::FT_Pixel_Mode__FT_PIXEL_MODE_MONO, PixelFormat::R8)
+            | (ffi::FT_Pixel_Mode__FT_PIXEL_MODE_MONO, PixelFormat::RGB8)
+            | (ffi::FT_Pixel_Mode__FT_PIXEL_MODE_MONO, PixelFormat::RGBA8) =>
+        {
+            for row in rows {
+                for x in 0 .. width {
+                    let c = if row[x / 8] & (0x80 >> (x % 8)) != 0 { 255 } else { 0 };
+                    pixels.extend(::std::iter::repeat_n(c, depth));
+                }
+            }
+        }
+        _ => return None,
+    }
+    Some(pixels)
+}
+
+/// Weights of the source pixels covered by each of `dst` pixels spanning
+/// the same length as `src` pixels.
+fn box_weights(src: usize, dst: usize) -> Vec<Vec<(usize, f32)>>{
+    let ratio = src as f32 / dst as f32;
+    (0 .. dst).map(|d| {
+        let start = d as f32 * ratio;
+        let end = start + ratio;
+        let mut weights = vec![];
+        let mut s = start.floor() as usize;
+        while (s as f32) < end && s < src {
+            let overlap = end.min(s as f32 + 1.) - start.max(s as f32);
+            if overlap > 0. {
+                weights.push((s, overlap / ratio));
+            }
+            s += 1;
+        }
+        weights
+    }).collect()
+}
+
+/// Scales `width` x `height` packed pixels of `depth` bytes by `scale`,
+/// averaging the source pixels each destination pixel covers, which keeps
+/// premultiplied colors correct. Returns the pixels and their size.
+fn resample(data: &[u8], width: usize, height: usize, depth: usize, scale: f32) -> (Vec<u8>, usize, usize){
+    if width == 0 || height == 0 {
+        return (vec![], 0, 0)
+    }
+    if scale == 1. {
+        return (data.to_vec(), width, height)
+    }
+    let dst_width = ((width as f32 * scale).round() as usize).max(1);
+    let dst_height = ((height as f32 * scale).round() as usize).max(1);
+
+    let columns = box_weights(width, dst_width);
+    let mut horizontal = vec![0f32; dst_width * height * depth];
+    for y in 0 .. height {
+        for (x, weights) in columns.iter().enumerate() {
+            for &(s, w) in weights {
+                for c in 0 .. depth {
+                    horizontal[(y * dst_width + x) * depth + c] += data[(y * width + s) * depth + c] as f32 * w;
+                }
+            }
+        }
+    }
+
+    let rows = box_weights(height, dst_height);
+    let mut pixels = vec![0f32; dst_width * dst_height * depth];
+    for (y, weights) in rows.iter().enumerate() {
+        for &(s, w) in weights {
+            let src = &horizontal[s * dst_width * depth .. (s + 1) * dst_width * depth];
+            let dst = &mut pixels[y * dst_width * depth .. (y + 1) * dst_width * depth];
+            for (d, v) in dst.iter_mut().zip(src) {
+                *d += v * w;
+            }
+        }
+    }
+    let pixels = pixels.into_iter().map(|v| v.round().min(255.) as u8).collect();
+    (pixels, dst_width, dst_height)
+}
+
+/// Scales `width` x `height` packed pixels of `depth` bytes by `scale`,
+/// taking the nearest source pixel so monochrome bitmaps stay sharp.
+/// Returns the pixels and their size.
+fn resample_nearest(data: &[u8], width: usize, height: usize, depth: usize, scale: f32) -> (Vec<u8>, usize, usize){
+    if width == 0 || height == 0 {
+        return (vec![], 0, 0)
+    }
+    if scale == 1. {
+        return (data.to_vec(), width, height)
+    }
+    let dst_width = ((width as f32 * scale).round() as usize).max(1);
+    let dst_height = ((height as f32 * scale).round() as usize).max(1);
+    let mut pixels = Vec::with_capacity(dst_width * dst_height * depth);
+    for y in 0 .. dst_height {
+        let sy = ((y as f32 + 0.5) / scale) as usize;
+        for x in 0 .. dst_width {
+            let sx = ((x as f32 + 0.5) / scale) as usize;
+            let i = (sy.min(height - 1) * width + sx.min(width - 1)) * depth;
+            pixels.extend_from_slice(&data[i .. i + depth]);
+        }
+    }
+    (pixels, dst_width, dst_height)
+}
+
+/// Distance field values as bytes, or native endian f32 for `R32F` atlases.
+fn field_to_pixels(field: &DistanceField, format: PixelFormat) -> Vec<u8>{
+    if format == PixelFormat::R32F {
+        field.data.iter().flat_map(|v| v.to_ne_bytes().to_vec()).collect()
+    }else{
+        field.data.iter().map(|v| (v.clamp(0., 1.) * 255.).round() as u8).collect()
+    }
+}
+
+/// Bitmap of a distance field converted by `field_to_pixels`.
+fn field_bitmap<'a>(field: &DistanceField, pixels: &'a [u8]) -> Bitmap<'a>{
+    Bitmap{
+        data: pixels,
+        width: field.width,
+        height: field.height,
+        pitch: field.width * field.channels * (pixels.len() / field.data.len().max(1)),
+        left: field.left,
+        top: field.top,
+    }
+}
+
+/// Sets up the font's library to filter LCD rendered glyphs with `filter`.
+unsafe fn set_lcd_filter(font: *mut ffi::texture_font_t, filter: LcdFilter){
+    let library = (*font).library;
+    match filter {
+        LcdFilter::None => {
+            ffi::FT_Library_SetLcdFilter(library, ffi::FT_LcdFilter__FT_LCD_FILTER_NONE);
+        }
+        LcdFilter::Light => {
+            ffi::FT_Library_SetLcdFilter(library, ffi::FT_LcdFilter__FT_LCD_FILTER_LIGHT);
+        }
+        LcdFilter::Legacy => {
+            ffi::FT_Library_SetLcdFilter(library, ffi::FT_LcdFilter__FT_LCD_FILTER_LEGACY);
+        }
+        LcdFilter::FreeType => {
+            ffi::FT_Library_SetLcdFilter(library, ffi::FT_LcdFilter__FT_LCD_FILTER_DEFAULT);
+        }
+        LcdFilter::Default | LcdFilter::Custom(_) => {
+            // the font's weights are already set to the ones for this filter
+            ffi::FT_Library_SetLcdFilter(library, ffi::FT_LcdFilter__FT_LCD_FILTER_DEFAULT);
+            ffi::FT_Library_SetLcdFilterWeights(library, (*font).lcd_weights.as_mut_ptr());
+        }
+    }
+}
+
+/// Loads the glyph `glyph_id` in the face's slot with `flags`, applying the
+/// synthetic styles of `options` and the subpixel `phase`, everything scaled
+/// by `scale`. Returns None on failure.
+pub(crate) unsafe fn load_styled(
+    face: ffi::FT_Face,
+    glyph_id: u32,
+    flags: ffi::FT_Int32,
+    phase: u8,
+    scale: usize,
+    options: &RasterOptions) -> Option<ffi::FT_GlyphSlot>
+{
+    let transformed = options.oblique != 0. || phase != 0 || scale != 1;
+    if transformed {
+        let fixed_scale = scale as ffi::FT_Fixed * 0x10000;
+        let mut matrix = ffi::FT_Matrix{
+            xx: fixed_scale,
+            xy: (options.oblique * fixed_scale as f32) as ffi::FT_Fixed,
+            yx: 0,
+            yy: fixed_scale,
+        };
+        let mut delta = ffi::FT_Vector{
+            x: if phase != 0 {
+                phase as ffi::FT_Pos * 64 * scale as ffi::FT_Pos / options.subpixel_phases as ffi::FT_Pos
+            }else{
+                0
+            },
+            y: 0,
+        };
+        ffi::FT_Set_Transform(face, &mut matrix, &mut delta);
+    }
+    let error = ffi::FT_Load_Glyph(face, glyph_id, flags);
+    if transformed {
+        ffi::FT_Set_Transform(face, ptr::null_mut(), ptr::null_mut());
+    }
+    if error != 0 {
+        return None
+    }
+
+    let slot = (*face).glyph;
+    if options.embolden != 0 && (*slot).format == ffi::FT_Glyph_Format__FT_GLYPH_FORMAT_OUTLINE {
+        let strength = options.embolden * scale as ffi::FT_Pos;
+        ffi::FT_Outline_EmboldenXY(&mut (*slot).outline, strength, strength);
+    }
+    Some(slot)
+}
+
+/// Loads, renders and packs in the atlas the glyph `key` applying `options`.
+/// Follows freetype-gl's choice of LCD rendering. Returns None on failure.
+pub(crate) unsafe fn load_glyph(
+    font: *mut ffi::texture_font_t,
+    codepoint: u32,
+    key: GlyphKey,
+    options: &RasterOptions) -> Option<TextureGlyph>
+{
+    if let Some(strike) = options.strike {
+        return load_strike_glyph(font, codepoint, key.glyph_id, &strike, options.format)
+    }
+    let face = (*font).face;
+
+    let mut flags = ffi::FT_LOAD_NO_BITMAP as ffi::FT_Int32 | options.hinting.load_flags();
+    let msdf = options.field_source == FieldSource::MultiChannel;
+    let lcd = options.format == PixelFormat::RGB8 && !msdf;
+    let render_mode = if lcd {
+        // the LCD target replaces the hinting one
+        flags = flags & !load_target(15) | load_target(ffi::FT_Render_Mode__FT_RENDER_MODE_LCD);
+        set_lcd_filter(font, options.lcd_filter);
+        ffi::FT_Render_Mode__FT_RENDER_MODE_LCD
+    }else{
+        ffi::FT_Render_Mode__FT_RENDER_MODE_NORMAL
+    };
+
+    // Distance fields can be rasterized upsampled, scaling everything by
+    // `scale` until they are downsampled back
+    let sdf = if (*font).rendermode == ffi::rendermode_t_RENDER_SIGNED_DISTANCE_FIELD {
+        Some(options.sdf.unwrap_or_default())
+    }else{
+        None
+    };
+    let scale = match sdf {
+        Some(sdf) if options.field_source == FieldSource::Coverage => sdf.scale(),
+        _ => 1,
+    };
+
+    #[cfg(feature = "color")]
+    if options.format == PixelFormat::RGBA8 && (*font).rendermode == ffi::rendermode_t_RENDER_NORMAL {
+        let layers = color::layers(face, key.glyph_id);
+        if !layers.is_empty() {
+            return color::load_color_glyph(font, codepoint, key, &layers, flags, options)
+        }
+    }
+
+    let slot = load_styled(face, key.glyph_id, flags, key.phase, scale, options)?;
+    // distance fields already have their own border
+    let no_padding = Padding{ left: 0, top: 0, right: 0, bottom: 0 };
+    let mut converted = vec![];
+    let stroked;
+    let (bitmap, padding) = if sdf.is_some() && options.field_source != FieldSource::Coverage {
+        if (*slot).format != ffi::FT_Glyph_Format__FT_GLYPH_FORMAT_OUTLINE {
+            return None
+        }
+        let shape = Shape::from_outline(&mut (*slot).outline, 1. / 64.);
+        if shape.is_empty() {
+            (Bitmap{ data: &[], width: 0, height: 0, pitch: 0, left: 0, top: 0 }, DEFAULT_PADDING)
+        }else{
+            let sdf = sdf.unwrap_or_default();
+            let field = if msdf {
+                msdf::distance_field(&shape, &sdf)
+            }else{
+                sdf::outline_distance_field(&shape, &sdf)
+            };
+            converted = field_to_pixels(&field, options.format);
+            (field_bitmap(&field, &converted), no_padding)
+        }
+    }else if options.fill_and_outline {
+        if (*slot).format != ffi::FT_Glyph_Format__FT_GLYPH_FORMAT_OUTLINE {
+            return None
+        }
+        // stroking works on a copy of the outline, so the slot can be
+        // rendered afterwards
+        stroked = stroke::stroke_glyph(font, slot, &options.stroke, render_mode)?;
+        if ffi::FT_Render_Glyph(slot, render_mode) != 0 {
+            return None
+        }
+        let fill = Bitmap::from_slot(slot, 1);
+        let outline = stroked.bitmap(1);
+        (layer_coverage(&[&fill, &outline], options.format.depth(), &mut converted), DEFAULT_PADDING)
+    }else{
+        let depth = if lcd { 3 } else { 1 };
+        let coverage = match (*font).rendermode {
+            ffi::rendermode_t_RENDER_OUTLINE_EDGE
+                | ffi::rendermode_t_RENDER_OUTLINE_POSITIVE
+                | ffi::rendermode_t_RENDER_OUTLINE_NEGATIVE =>
+            {
+                if (*slot).format != ffi::FT_Glyph_Format__FT_GLYPH_FORMAT_OUTLINE {
+                    return None
+                }
+                stroked = stroke::stroke_glyph(font, slot, &options.stroke, render_mode)?;
+                stroked.bitmap(depth)
+            }
+            _ => {
+                if ffi::FT_Render_Glyph(slot, render_mode) != 0 {
+                    return None
+                }
+                Bitmap::from_slot(slot, depth)
+            }
+        };
+        match (options.format, sdf) {
+            (_, Some(sdf)) if coverage.width > 0 && coverage.height > 0 => {
+                let field = sdf::distance_field(&coverage, &sdf);
+                converted = field_to_pixels(&field, options.format);
+                (field_bitmap(&field, &converted), no_padding)
+            }
+            (PixelFormat::RGBA8, None) => {
+                converted = coverage_to_rgba(&coverage);
+                let bitmap = Bitmap{
+                    data: &converted,
+                    pitch: coverage.width * 4,
+                    ..coverage
+                };
+                (bitmap, DEFAULT_PADDING)
+            }
+            _ => (coverage, DEFAULT_PADDING),
+        }
+    };
+    let glyph = pack_glyph(font, codepoint, key.glyph_id, &bitmap, padding);
+    if glyph.is_null() {
+        return None
+    }
+    // Unhinted advance as freetype-gl does, widened by the emboldening
+    (*glyph).advance_x = (*slot).linearHoriAdvance as f32 / 65536. + options.embolden as f32 / 64.;
+    (*glyph).advance_y = (*slot).advance.y as f32 / 64. / scale as f32;
+    Some(TextureGlyph{ glyph, colored: false })
+}
+
+/// Loads the bitmap of the glyph `glyph_id` from the selected `strike`,
+/// scales it to the font's size and packs it in the atlas. Color bitmaps are
+/// only loaded for `RGBA8` atlases, monochrome ones are scaled without
+/// filtering. Returns None on failure.
+unsafe fn load_strike_glyph(
+    font: *mut ffi::texture_font_t,
+    codepoint: u32,
+    glyph_id: u32,
+    strike: &Strike,
+    format: PixelFormat) -> Option<TextureGlyph>
+{
+    let face = (*font).face;
+    let flags = if format == PixelFormat::RGBA8 { ffi::FT_LOAD_COLOR } else { ffi::FT_LOAD_DEFAULT };
+    if ffi::FT_Load_Glyph(face, glyph_id, flags as ffi::FT_Int32) != 0 {
+        return None
+    }
+    let slot = (*face).glyph;
+    // Strikes are already rendered, this only renders the odd outline glyph
+    if ffi::FT_Render_Glyph(slot, ffi::FT_Render_Mode__FT_RENDER_MODE_NORMAL) != 0 {
+        return None
+    }
+    let bitmap = &(*slot).bitmap;
+    let colored = bitmap.pixel_mode as ffi::FT_Pixel_Mode == ffi::FT_Pixel_Mode__FT_PIXEL_MODE_BGRA;
+    let mono = bitmap.pixel_mode as ffi::FT_Pixel_Mode == ffi::FT_Pixel_Mode__FT_PIXEL_MODE_MONO;
+    let pixels = strike_pixels(bitmap, format)?;
+    let depth = format.depth();
+    let (pixels, width, height) = if mono {
+        resample_nearest(&pixels, bitmap.width as usize, bitmap.rows as usize, depth, strike.scale)
+    }else{
+        resample(&pixels, bitmap.width as usize, bitmap.rows as usize, depth, strike.scale)
+    };
+    let scaled = Bitmap{
+        data: &pixels,
+        width,
+        height,
+        pitch: width * depth,
+        left: ((*slot).bitmap_left as f32 * strike.scale).round() as i32,
+        top: ((*slot).bitmap_top as f32 * strike.scale).round() as i32,
+    };
+    let glyph = pack_glyph(font, codepoint, glyph_id, &scaled, DEFAULT_PADDING);
+    if glyph.is_null() {
+        return None
+    }
+    (*glyph).advance_x = (*slot).advance.x as f32 / 64. * strike.scale;
+    (*glyph).advance_y = (*slot).advance.y as f32 / 64. * strike.scale;
+    Some(TextureGlyph{ glyph, colored })
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn options(subpixel_phases: u8) -> RasterOptions{
+        RasterOptions{ subpixel_phases, ..RasterOptions::default() }
+    }
+
+    #[test]
+    fn snap_without_subpixel_positions(){
+        assert_eq!(options(0).snap(10.3), (10.3, 0));
+        assert_eq!(options(1).snap(10.3), (10.3, 0));
+    }
+
+    #[test]
+    fn snap_to_nearest_phase(){
+        let options = options(4);
+        assert_eq!(options.snap(10.3), (10., 1));
+        assert_eq!(options.snap(10.5), (10., 2));
+        // rounds up to the next whole pixel
+        assert_eq!(options.snap(10.9), (11., 0));
+        assert_eq!(options.snap(-0.3), (-1., 3));
+    }
+
+    #[test]
+    fn box_weights_downscale(){
+        assert_eq!(box_weights(4, 2), vec![vec![(0, 0.5), (1, 0.5)], vec![(2, 0.5), (3, 0.5)]]);
+    }
+
+    #[test]
+    fn box_weights_upscale(){
+        assert_eq!(box_weights(2, 4), vec![vec![(0, 1.)], vec![(0, 1.)], vec![(1, 1.)], vec![(1, 1.)]]);
+    }
+
+    #[test]
+    fn box_weights_sum_to_one(){
+        for &(src, dst) in &[(5, 3), (3, 5), (7, 7), (13, 4)] {
+            for weights in box_weights(src, dst) {
+                let sum = weights.iter().map(|&(_, w)| w).sum::<f32>();
+                assert!((sum - 1.).abs() < 1e-5, "{} -> {}: {}", src, dst, sum);
+            }
+        }
+    }
+
+    #[test]
+    fn resample_averages(){
+        assert_eq!(resample(&[0, 100, 200, 100], 2, 2, 1, 0.5), (vec![100], 1, 1));
+        // per channel
+        assert_eq!(resample(&[0, 255, 100, 55], 2, 1, 2, 0.5), (vec![50, 155], 1, 1));
+    }
+
+    #[test]
+    fn resample_unscaled_and_empty(){
+        assert_eq!(resample(&[1, 2, 3], 3, 1, 1, 1.), (vec![1, 2, 3], 3, 1));
+        assert_eq!(resample(&[], 0, 0, 1, 2.), (vec![], 0, 0));
+    }
+
+    #[test]
+    fn resample_upscale(){
+        assert_eq!(resample(&[7], 1, 1, 1, 2.), (vec![7; 4], 2, 2));
+    }
+
+    #[test]
+    fn resample_nearest_keeps_coverage_binary(){
+        assert_eq!(resample_nearest(&[255, 0], 2, 1, 1, 2.), (vec![255, 255, 0, 0, 255, 255, 0, 0], 4, 2));
+        let (pixels, width, height) = resample_nearest(&[255, 0, 0, 255], 2, 2, 1, 1.5);
+        assert_eq!((width, height), (3, 3));
+        assert!(pixels.iter().all(|&c| c == 0 || c == 255));
+    }
+}
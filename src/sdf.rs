@@ -0,0 +1,287 @@
+use raster::Bitmap;
+use shape::{Shape, Vector};
+
+/// Signed distance fields generated from rust instead of freetype-gl's edtaa3.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SdfOptions{
+    /// Distance in pixels from the outline at which the field saturates. Glyphs
+    /// are padded by as many whole pixels in the atlas.
+    pub spread: f32,
+    /// Glyphs are rasterized at `upsample` times their size and the field
+    /// downsampled, which gives more accurate distances. 1 disables it.
+    pub upsample: u8,
+}
+
+impl Default for SdfOptions{
+    fn default() -> SdfOptions{
+        SdfOptions{
+            spread: 4.,
+            upsample: 1,
+        }
+    }
+}
+
+impl SdfOptions{
+    /// Upsampling factor, at least 1.
+    #[inline]
+    pub(crate) fn scale(&self) -> usize{
+        self.upsample.max(1) as usize
+    }
+
+    /// Padding around glyphs in the atlas, in pixels.
+    #[inline]
+    pub(crate) fn padding(&self) -> usize{
+        self.spread.max(0.).ceil() as usize
+    }
+}
+
+/// What distance fields are measured from.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) enum FieldSource{
+    /// The rasterized glyph, upsampled as set in `SdfOptions`.
+    #[default]
+    Coverage,
+    /// The exact glyph outline.
+    Outline,
+    /// The glyph outline, with a distance per channel to keep corners sharp.
+    MultiChannel,
+}
+
+/// A distance field mapped to 0 outside of the glyph at `spread` pixels from
+/// the outline, 0.5 on it and 1 inside at `spread` pixels. Values go past that
+/// range further away from the outline.
+pub(crate) struct DistanceField{
+    pub data: Vec<f32>,
+    /// Values per pixel, 3 for multi-channel fields.
+    pub channels: usize,
+    pub width: usize,
+    pub height: usize,
+    /// Left bearing in pixels.
+    pub left: i32,
+    /// Top bearing in pixels.
+    pub top: i32,
+}
+
+const INF: f32 = 1e20;
+
+/// Squared euclidean distance transform of the sampled function `f` as
+/// described by Felzenszwalb and Huttenlocher. `v` and `z` are scratch space
+/// of at least `f.len()` and `f.len() + 1` elements.
+fn edt_1d(f: &[f32], d: &mut [f32], v: &mut [usize], z: &mut [f32]){
+    let n = f.len();
+    let mut k = 0;
+    v[0] = 0;
+    z[0] = -INF;
+    z[1] = INF;
+    for q in 1 .. n {
+        let intersection = |r: usize| {
+            ((f[q] + (q * q) as f32) - (f[r] + (r * r) as f32)) / (2 * q - 2 * r) as f32
+        };
+        let mut s = intersection(v[k]);
+        while s <= z[k] {
+            k -= 1;
+            s = intersection(v[k]);
+        }
+        k += 1;
+        v[k] = q;
+        z[k] = s;
+        z[k + 1] = INF;
+    }
+    k = 0;
+    for (q, dq) in d.iter_mut().enumerate().take(n) {
+        while z[k + 1] < q as f32 {
+            k += 1;
+        }
+        let r = v[k];
+        let offset = q as f32 - r as f32;
+        *dq = offset * offset + f[r];
+    }
+}
+
+/// Squared distance from each pixel of the `width` x `height` grid to the
+/// nearest pixel where `feature` is true.
+fn edt_2d(feature: &[bool], width: usize, height: usize) -> Vec<f32>{
+    let mut grid = feature.iter().map(|&f| if f { 0. } else { INF }).collect::<Vec<_>>();
+    let n = width.max(height);
+    let mut f = vec![0.; n];
+    let mut d = vec![0.; n];
+    let mut v = vec![0; n];
+    let mut z = vec![0.; n + 1];
+    for x in 0 .. width {
+        for y in 0 .. height {
+            f[y] = grid[y * width + x];
+        }
+        edt_1d(&f[.. height], &mut d[.. height], &mut v, &mut z);
+        for y in 0 .. height {
+            grid[y * width + x] = d[y];
+        }
+    }
+    for y in 0 .. height {
+        let row = &mut grid[y * width .. (y + 1) * width];
+        f[.. width].copy_from_slice(row);
+        edt_1d(&f[.. width], &mut d[.. width], &mut v, &mut z);
+        row.copy_from_slice(&d[.. width]);
+    }
+    grid
+}
+
+/// Computes the distance field of the 8 bit `coverage`, rasterized at
+/// `options.upsample` times the glyph size, with bearings in those upsampled
+/// pixels. The field has one value per glyph pixel and is padded by
+/// `options.padding()` pixels on every side.
+pub(crate) fn distance_field(coverage: &Bitmap, options: &SdfOptions) -> DistanceField{
+    let scale = options.scale();
+    let padding = options.padding();
+    let div_floor = |a: i32| (a as f32 / scale as f32).floor() as i32;
+    let div_ceil = |a: i32| (a as f32 / scale as f32).ceil() as i32;
+
+    // Align the upsampled bitmap with the pixel grid of the field
+    let left = div_floor(coverage.left) - padding as i32;
+    let top = div_ceil(coverage.top) + padding as i32;
+    let offset_x = (coverage.left - left * scale as i32) as usize;
+    let offset_y = (top * scale as i32 - coverage.top) as usize;
+    let width = (offset_x + coverage.width + padding * scale).div_ceil(scale);
+    let height = (offset_y + coverage.height + padding * scale).div_ceil(scale);
+
+    let hi_width = width * scale;
+    let hi_height = height * scale;
+    let mut inside = vec![false; hi_width * hi_height];
+    for row in 0 .. coverage.height {
+        let src = &coverage.data[row * coverage.pitch .. row * coverage.pitch + coverage.width];
+        let dst = (row + offset_y) * hi_width + offset_x;
+        for (x, &c) in src.iter().enumerate() {
+            inside[dst + x] = c >= 128;
+        }
+    }
+    let outside = inside.iter().map(|&i| !i).collect::<Vec<_>>();
+    let to_inside = edt_2d(&inside, hi_width, hi_height);
+    let to_outside = edt_2d(&outside, hi_width, hi_height);
+
+    // The outline lies half way between inside and outside pixel centers
+    let signed = to_inside.iter().zip(&to_outside).map(|(&i, &o)| {
+        if i > 0. {
+            i.sqrt() - 0.5
+        }else{
+            0.5 - o.sqrt()
+        }
+    }).collect::<Vec<_>>();
+
+    let spread = options.spread.max(1. / scale as f32);
+    let mut data = Vec::with_capacity(width * height);
+    for y in 0 .. height {
+        for x in 0 .. width {
+            let mut sum = 0.;
+            for sy in 0 .. scale {
+                let row = (y * scale + sy) * hi_width + x * scale;
+                sum += signed[row .. row + scale].iter().sum::<f32>();
+            }
+            let distance = sum / (scale * scale * scale) as f32;
+            data.push(0.5 - distance / (2. * spread));
+        }
+    }
+
+    DistanceField{
+        data,
+        channels: 1,
+        width,
+        height,
+        left,
+        top,
+    }
+}
+
+/// Pixel grid covering `shape` padded by `padding` pixels, as left and top
+/// bearings, width and height.
+pub(crate) fn outline_grid(shape: &Shape, padding: usize) -> (i32, i32, usize, usize){
+    let padding = padding as i32;
+    let (x0, y0, x1, y1) = shape.control_box();
+    let left = x0.floor() as i32 - padding;
+    let top = y1.ceil() as i32 + padding;
+    let width = (x1.ceil() as i32 + padding - left) as usize;
+    let height = (top - (y0.floor() as i32 - padding)) as usize;
+    (left, top, width, height)
+}
+
+/// Computes the distance field of `shape`, in pixels with y pointing up, from
+/// the true distance to its lines and curves.
+pub(crate) fn outline_distance_field(shape: &Shape, options: &SdfOptions) -> DistanceField{
+    let (left, top, width, height) = outline_grid(shape, options.padding());
+    let spread = options.spread.max(1.) as f64;
+    let mut data = Vec::with_capacity(width * height);
+    for y in 0 .. height {
+        for x in 0 .. width {
+            let p = Vector::new(left as f64 + x as f64 + 0.5, top as f64 - y as f64 - 0.5);
+            let distance = shape.contours.iter()
+                .flat_map(|c| c.iter())
+                .map(|segment| segment.signed_distance(p).0.distance.abs())
+                .fold(f64::MAX, f64::min);
+            let distance = if shape.contains(p) { distance } else { -distance };
+            data.push((0.5 + distance / (2. * spread)) as f32);
+        }
+    }
+
+    DistanceField{
+        data,
+        channels: 1,
+        width,
+        height,
+        left,
+        top,
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn padding_and_scale(){
+        let options = |spread, upsample| SdfOptions{ spread, upsample };
+        assert_eq!(options(4., 1).padding(), 4);
+        assert_eq!(options(2.5, 1).padding(), 3);
+        assert_eq!(options(-1., 1).padding(), 0);
+        assert_eq!(options(4., 0).scale(), 1);
+        assert_eq!(options(4., 3).scale(), 3);
+    }
+
+    #[test]
+    fn edt_1d_single_feature(){
+        let f = [INF, INF, 0., INF];
+        let mut d = [0.; 4];
+        edt_1d(&f, &mut d, &mut [0; 4], &mut [0.; 5]);
+        assert_eq!(d, [4., 1., 0., 1.]);
+    }
+
+    #[test]
+    fn edt_2d_single_feature(){
+        let mut feature = vec![false; 25];
+        feature[2 * 5 + 2] = true;
+        let d = edt_2d(&feature, 5, 5);
+        assert_eq!(d[2 * 5 + 2], 0.);
+        assert_eq!(d[2 * 5 + 3], 1.);
+        assert_eq!(d[4 * 5 + 2], 4.);
+        assert_eq!(d[0], 8.);
+        assert_eq!(d[4 * 5 + 3], 5.);
+    }
+
+    #[test]
+    fn distance_field_of_a_single_pixel(){
+        let coverage = Bitmap{ data: &[255], width: 1, height: 1, pitch: 1, left: 0, top: 1 };
+        let field = distance_field(&coverage, &SdfOptions{ spread: 2., upsample: 1 });
+        // padded by the spread on every side
+        assert_eq!((field.width, field.height, field.left, field.top), (5, 5, -2, 3));
+        let at = |x: usize, y: usize| field.data[y * field.width + x];
+        // half a pixel inside and outside of the outline
+        assert!((at(2, 2) - 0.625).abs() < 1e-6);
+        assert!((at(2, 1) - 0.375).abs() < 1e-6);
+        assert!((at(0, 0) - (0.5 - (8f32.sqrt() - 0.5) / 4.)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn upsampled_field_size(){
+        let data = vec![255; 4 * 4];
+        let coverage = Bitmap{ data: &data, width: 4, height: 4, pitch: 4, left: 0, top: 4 };
+        let field = distance_field(&coverage, &SdfOptions{ spread: 1., upsample: 2 });
+        assert_eq!((field.width, field.height, field.left, field.top), (4, 4, -1, 3));
+    }
+}
@@ -0,0 +1,714 @@
+use std::f64::consts::PI;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::os::raw::{c_int, c_void};
+
+use ffi;
+
+/// A point or direction in a glyph outline, with y pointing up.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct Vector{
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Vector{
+    #[inline]
+    pub fn new(x: f64, y: f64) -> Vector{
+        Vector{ x, y }
+    }
+
+    #[inline]
+    pub fn dot(self, other: Vector) -> f64{
+        self.x * other.x + self.y * other.y
+    }
+
+    #[inline]
+    pub fn cross(self, other: Vector) -> f64{
+        self.x * other.y - self.y * other.x
+    }
+
+    #[inline]
+    pub fn length(self) -> f64{
+        self.dot(self).sqrt()
+    }
+
+    #[inline]
+    pub fn is_zero(self) -> bool{
+        self.x == 0. && self.y == 0.
+    }
+
+    /// Unit vector in the same direction, pointing up for the zero vector.
+    pub fn normalize(self) -> Vector{
+        let length = self.length();
+        if length == 0. {
+            Vector::new(0., 1.)
+        }else{
+            Vector::new(self.x / length, self.y / length)
+        }
+    }
+
+    #[inline]
+    fn mix(self, other: Vector, t: f64) -> Vector{
+        self + (other - self) * t
+    }
+}
+
+impl Add for Vector{
+    type Output = Vector;
+    #[inline]
+    fn add(self, other: Vector) -> Vector{
+        Vector::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Vector{
+    type Output = Vector;
+    #[inline]
+    fn sub(self, other: Vector) -> Vector{
+        Vector::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<f64> for Vector{
+    type Output = Vector;
+    #[inline]
+    fn mul(self, s: f64) -> Vector{
+        Vector::new(self.x * s, self.y * s)
+    }
+}
+
+impl Neg for Vector{
+    type Output = Vector;
+    #[inline]
+    fn neg(self) -> Vector{
+        Vector::new(-self.x, -self.y)
+    }
+}
+
+#[inline]
+fn non_zero_sign(x: f64) -> f64{
+    if x > 0. { 1. } else { -1. }
+}
+
+/// Distance to a segment, signed by the side of the segment the point is on.
+/// `dot` breaks ties between segments meeting at a corner: the one the point
+/// is more perpendicular to is the closest.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SignedDistance{
+    pub distance: f64,
+    pub dot: f64,
+}
+
+impl SignedDistance{
+    pub const INFINITE: SignedDistance = SignedDistance{ distance: -1e240, dot: 1. };
+
+    #[inline]
+    pub fn closer_than(&self, other: &SignedDistance) -> bool{
+        self.distance.abs() < other.distance.abs()
+            || (self.distance.abs() == other.distance.abs() && self.dot < other.dot)
+    }
+}
+
+/// Real roots of a x² + b x + c.
+fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64>{
+    if a == 0. || b.abs() > 1e12 * a.abs() {
+        if b == 0. {
+            return vec![]
+        }
+        return vec![-c / b]
+    }
+    let discriminant = b * b - 4. * a * c;
+    if discriminant > 0. {
+        let discriminant = discriminant.sqrt();
+        vec![(-b + discriminant) / (2. * a), (-b - discriminant) / (2. * a)]
+    }else if discriminant == 0. {
+        vec![-b / (2. * a)]
+    }else{
+        vec![]
+    }
+}
+
+/// Real roots of x³ + a x² + b x + c.
+fn solve_cubic_normed(a: f64, b: f64, c: f64) -> Vec<f64>{
+    let a2 = a * a;
+    let q = (a2 - 3. * b) / 9.;
+    let r = (a * (2. * a2 - 9. * b) + 27. * c) / 54.;
+    let r2 = r * r;
+    let q3 = q * q * q;
+    let a = a / 3.;
+    if r2 < q3 {
+        let t = (r / q3.sqrt()).clamp(-1., 1.).acos();
+        let q = -2. * q.sqrt();
+        vec![
+            q * (t / 3.).cos() - a,
+            q * ((t + 2. * PI) / 3.).cos() - a,
+            q * ((t - 2. * PI) / 3.).cos() - a,
+        ]
+    }else{
+        let u = -r.signum() * (r.abs() + (r2 - q3).sqrt()).cbrt();
+        let v = if u == 0. { 0. } else { q / u };
+        if u == v || (u - v).abs() < 1e-12 * (u + v).abs() {
+            vec![u + v - a, -0.5 * (u + v) - a]
+        }else{
+            vec![u + v - a]
+        }
+    }
+}
+
+/// Real roots of a x³ + b x² + c x + d.
+fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64>{
+    if a != 0. {
+        let bn = b / a;
+        if bn.abs() < 1e6 {
+            return solve_cubic_normed(bn, c / a, d / a)
+        }
+    }
+    solve_quadratic(b, c, d)
+}
+
+/// A line or bezier curve of a contour.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Segment{
+    Line([Vector; 2]),
+    Quad([Vector; 3]),
+    Cubic([Vector; 4]),
+}
+
+impl Segment{
+    pub fn start(&self) -> Vector{
+        match *self {
+            Segment::Line(p) => p[0],
+            Segment::Quad(p) => p[0],
+            Segment::Cubic(p) => p[0],
+        }
+    }
+
+    pub fn end(&self) -> Vector{
+        match *self {
+            Segment::Line(p) => p[1],
+            Segment::Quad(p) => p[2],
+            Segment::Cubic(p) => p[3],
+        }
+    }
+
+    /// All the points defining the segment, including control points.
+    pub fn points(&self) -> &[Vector]{
+        match *self {
+            Segment::Line(ref p) => p,
+            Segment::Quad(ref p) => p,
+            Segment::Cubic(ref p) => p,
+        }
+    }
+
+    /// Point at parameter `t`, between 0 and 1.
+    pub fn point(&self, t: f64) -> Vector{
+        match *self {
+            Segment::Line(p) => p[0].mix(p[1], t),
+            Segment::Quad(p) => p[0].mix(p[1], t).mix(p[1].mix(p[2], t), t),
+            Segment::Cubic(p) => {
+                let p12 = p[1].mix(p[2], t);
+                p[0].mix(p[1], t).mix(p12, t).mix(p12.mix(p[2].mix(p[3], t), t), t)
+            }
+        }
+    }
+
+    /// Tangent at parameter `t`, not normalized.
+    pub fn direction(&self, t: f64) -> Vector{
+        match *self {
+            Segment::Line(p) => p[1] - p[0],
+            Segment::Quad(p) => {
+                let tangent = (p[1] - p[0]).mix(p[2] - p[1], t);
+                if tangent.is_zero() { p[2] - p[0] } else { tangent }
+            }
+            Segment::Cubic(p) => {
+                let tangent = (p[1] - p[0]).mix(p[2] - p[1], t).mix((p[2] - p[1]).mix(p[3] - p[2], t), t);
+                if !tangent.is_zero() {
+                    tangent
+                }else if t == 0. {
+                    p[2] - p[0]
+                }else if t == 1. {
+                    p[3] - p[1]
+                }else{
+                    tangent
+                }
+            }
+        }
+    }
+
+    /// Splits the segment in three segments of equal parameter range.
+    pub fn split_in_thirds(&self) -> [Segment; 3]{
+        let (a, b) = (self.point(1. / 3.), self.point(2. / 3.));
+        match *self {
+            Segment::Line(p) => [Segment::Line([p[0], a]), Segment::Line([a, b]), Segment::Line([b, p[1]])],
+            Segment::Quad(p) => [
+                Segment::Quad([p[0], p[0].mix(p[1], 1. / 3.), a]),
+                Segment::Quad([a, p[0].mix(p[1], 5. / 9.).mix(p[1].mix(p[2], 4. / 9.), 0.5), b]),
+                Segment::Quad([b, p[1].mix(p[2], 2. / 3.), p[2]]),
+            ],
+            Segment::Cubic(p) => {
+                let third = |t: f64| {
+                    (
+                        p[0].mix(p[1], t).mix(p[1].mix(p[2], t), t),
+                        p[1].mix(p[2], t).mix(p[2].mix(p[3], t), t),
+                    )
+                };
+                let (l1, r1) = third(1. / 3.);
+                let (l2, r2) = third(2. / 3.);
+                [
+                    Segment::Cubic([p[0], if p[0] == p[1] { p[0] } else { p[0].mix(p[1], 1. / 3.) }, l1, a]),
+                    Segment::Cubic([a, l1.mix(r1, 2. / 3.), l2.mix(r2, 1. / 3.), b]),
+                    Segment::Cubic([b, r2, if p[2] == p[3] { p[3] } else { p[2].mix(p[3], 2. / 3.) }, p[3]]),
+                ]
+            }
+        }
+    }
+
+    /// Signed distance from `origin` to the segment and parameter of the
+    /// closest point, which is outside [0, 1] when the closest point is an
+    /// end point.
+    pub fn signed_distance(&self, origin: Vector) -> (SignedDistance, f64){
+        match *self {
+            Segment::Line(p) => {
+                let aq = origin - p[0];
+                let ab = p[1] - p[0];
+                let param = aq.dot(ab) / ab.dot(ab);
+                let eq = if param > 0.5 { p[1] } else { p[0] } - origin;
+                let endpoint_distance = eq.length();
+                if param > 0. && param < 1. {
+                    let normal = Vector::new(ab.y, -ab.x).normalize();
+                    let ortho_distance = normal.dot(aq);
+                    if ortho_distance.abs() < endpoint_distance {
+                        return (SignedDistance{ distance: ortho_distance, dot: 0. }, param)
+                    }
+                }
+                let distance = SignedDistance{
+                    distance: non_zero_sign(aq.cross(ab)) * endpoint_distance,
+                    dot: ab.normalize().dot(eq.normalize()).abs(),
+                };
+                (distance, param)
+            }
+            Segment::Quad(p) => {
+                let qa = p[0] - origin;
+                let ab = p[1] - p[0];
+                let br = p[2] - p[1] - ab;
+                let a = br.dot(br);
+                let b = 3. * ab.dot(br);
+                let c = 2. * ab.dot(ab) + qa.dot(br);
+                let d = qa.dot(ab);
+
+                let dir = self.direction(0.);
+                let mut min_distance = non_zero_sign(dir.cross(qa)) * qa.length();
+                let mut param = -qa.dot(dir) / dir.dot(dir);
+                let dir = self.direction(1.);
+                let distance = (p[2] - origin).length();
+                if distance < min_distance.abs() {
+                    min_distance = non_zero_sign(dir.cross(p[2] - origin)) * distance;
+                    param = (origin - p[1]).dot(dir) / dir.dot(dir);
+                }
+                for t in solve_cubic(a, b, c, d) {
+                    if t > 0. && t < 1. {
+                        let qe = qa + ab * (2. * t) + br * (t * t);
+                        let distance = qe.length();
+                        if distance <= min_distance.abs() {
+                            min_distance = non_zero_sign((ab + br * t).cross(qe)) * distance;
+                            param = t;
+                        }
+                    }
+                }
+                self.finish_distance(min_distance, param, qa, p[2] - origin)
+            }
+            Segment::Cubic(p) => {
+                const SEARCH_STARTS: usize = 4;
+                const SEARCH_STEPS: usize = 4;
+                let qa = p[0] - origin;
+                let ab = p[1] - p[0];
+                let br = p[2] - p[1] - ab;
+                let as_ = (p[3] - p[2]) - (p[2] - p[1]) - br;
+
+                let dir = self.direction(0.);
+                let mut min_distance = non_zero_sign(dir.cross(qa)) * qa.length();
+                let mut param = -qa.dot(dir) / dir.dot(dir);
+                let dir = self.direction(1.);
+                let distance = (p[3] - origin).length();
+                if distance < min_distance.abs() {
+                    min_distance = non_zero_sign(dir.cross(p[3] - origin)) * distance;
+                    param = (dir - (p[3] - origin)).dot(dir) / dir.dot(dir);
+                }
+                // Newton iterations from a few starting points along the curve
+                for i in 0 ..= SEARCH_STARTS {
+                    let mut t = i as f64 / SEARCH_STARTS as f64;
+                    let mut qe = qa + ab * (3. * t) + br * (3. * t * t) + as_ * (t * t * t);
+                    for _ in 0 .. SEARCH_STEPS {
+                        let d1 = ab * 3. + br * (6. * t) + as_ * (3. * t * t);
+                        let d2 = br * 6. + as_ * (6. * t);
+                        t -= qe.dot(d1) / (d1.dot(d1) + qe.dot(d2));
+                        if t <= 0. || t >= 1. {
+                            break
+                        }
+                        qe = qa + ab * (3. * t) + br * (3. * t * t) + as_ * (t * t * t);
+                        let distance = qe.length();
+                        if distance < min_distance.abs() {
+                            let d1 = ab * 3. + br * (6. * t) + as_ * (3. * t * t);
+                            min_distance = non_zero_sign(d1.cross(qe)) * distance;
+                            param = t;
+                        }
+                    }
+                }
+                self.finish_distance(min_distance, param, qa, p[3] - origin)
+            }
+        }
+    }
+
+    /// Curve distances closest to an end point get the dot product with the
+    /// tangent there to break ties with the next segment.
+    fn finish_distance(&self, distance: f64, param: f64, qa: Vector, qb: Vector) -> (SignedDistance, f64){
+        let dot = if (0. ..= 1.).contains(&param) {
+            0.
+        }else if param < 0.5 {
+            self.direction(0.).normalize().dot(qa.normalize()).abs()
+        }else{
+            self.direction(1.).normalize().dot(qb.normalize()).abs()
+        };
+        (SignedDistance{ distance, dot }, param)
+    }
+
+    /// Replaces `distance` by the distance to the tangent line extended past
+    /// the closest end point, if that's closer. Gives the pseudo distance
+    /// multi-channel fields are built from.
+    pub fn distance_to_pseudo_distance(&self, distance: &mut SignedDistance, origin: Vector, param: f64){
+        if param < 0. {
+            let dir = self.direction(0.).normalize();
+            let aq = origin - self.start();
+            if aq.dot(dir) < 0. {
+                let pseudo_distance = aq.cross(dir);
+                if pseudo_distance.abs() <= distance.distance.abs() {
+                    *distance = SignedDistance{ distance: pseudo_distance, dot: 0. };
+                }
+            }
+        }else if param > 1. {
+            let dir = self.direction(1.).normalize();
+            let bq = origin - self.end();
+            if bq.dot(dir) > 0. {
+                let pseudo_distance = bq.cross(dir);
+                if pseudo_distance.abs() <= distance.distance.abs() {
+                    *distance = SignedDistance{ distance: pseudo_distance, dot: 0. };
+                }
+            }
+        }
+    }
+
+    /// Approximates the segment by `steps` lines.
+    pub fn flatten(&self, steps: usize) -> impl Iterator<Item = (Vector, Vector)> + '_{
+        let steps = match *self {
+            Segment::Line(_) => 1,
+            _ => steps.max(1),
+        };
+        (0 .. steps).map(move |i| {
+            (self.point(i as f64 / steps as f64), self.point((i + 1) as f64 / steps as f64))
+        })
+    }
+}
+
+/// Closed loop of segments, each starting where the previous one ends.
+pub(crate) type Contour = Vec<Segment>;
+
+/// A glyph outline.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Shape{
+    pub contours: Vec<Contour>,
+}
+
+struct Decomposer{
+    contours: Vec<Contour>,
+    last: Vector,
+    scale: f64,
+}
+
+impl Decomposer{
+    unsafe fn point(&self, v: *const ffi::FT_Vector) -> Vector{
+        Vector::new((*v).x as f64 * self.scale, (*v).y as f64 * self.scale)
+    }
+
+    fn push(&mut self, segment: Segment, to: Vector){
+        // freetype closes contours with a line even if it has no length
+        let degenerate = segment.points().iter().all(|p| *p == to);
+        if !degenerate {
+            if let Some(contour) = self.contours.last_mut() {
+                contour.push(segment);
+            }
+        }
+        self.last = to;
+    }
+}
+
+unsafe extern "C" fn move_to(to: *const ffi::FT_Vector, user: *mut c_void) -> c_int{
+    let decomposer = &mut *(user as *mut Decomposer);
+    decomposer.contours.push(vec![]);
+    decomposer.last = decomposer.point(to);
+    0
+}
+
+unsafe extern "C" fn line_to(to: *const ffi::FT_Vector, user: *mut c_void) -> c_int{
+    let decomposer = &mut *(user as *mut Decomposer);
+    let to = decomposer.point(to);
+    decomposer.push(Segment::Line([decomposer.last, to]), to);
+    0
+}
+
+unsafe extern "C" fn conic_to(control: *const ffi::FT_Vector, to: *const ffi::FT_Vector, user: *mut c_void) -> c_int{
+    let decomposer = &mut *(user as *mut Decomposer);
+    let control = decomposer.point(control);
+    let to = decomposer.point(to);
+    decomposer.push(Segment::Quad([decomposer.last, control, to]), to);
+    0
+}
+
+unsafe extern "C" fn cubic_to(
+    control1: *const ffi::FT_Vector,
+    control2: *const ffi::FT_Vector,
+    to: *const ffi::FT_Vector,
+    user: *mut c_void) -> c_int
+{
+    let decomposer = &mut *(user as *mut Decomposer);
+    let control1 = decomposer.point(control1);
+    let control2 = decomposer.point(control2);
+    let to = decomposer.point(to);
+    decomposer.push(Segment::Cubic([decomposer.last, control1, control2, to]), to);
+    0
+}
+
+impl Shape{
+    /// Decomposes a freetype outline, multiplying its coordinates by `scale`,
+    /// eg. 1/64 to get pixels out of a scaled outline.
+    pub unsafe fn from_outline(outline: *mut ffi::FT_Outline, scale: f64) -> Shape{
+        let funcs = ffi::FT_Outline_Funcs{
+            move_to: Some(move_to),
+            line_to: Some(line_to),
+            conic_to: Some(conic_to),
+            cubic_to: Some(cubic_to),
+            shift: 0,
+            delta: 0,
+        };
+        let mut decomposer = Decomposer{
+            contours: vec![],
+            last: Vector::default(),
+            scale,
+        };
+        ffi::FT_Outline_Decompose(outline, &funcs, &mut decomposer as *mut Decomposer as *mut c_void);
+        decomposer.contours.retain(|contour| !contour.is_empty());
+        Shape{ contours: decomposer.contours }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool{
+        self.contours.is_empty()
+    }
+
+    /// Bounding box of all the points of the shape, including control points,
+    /// as (left, bottom, right, top).
+    pub fn control_box(&self) -> (f64, f64, f64, f64){
+        let mut bounds = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+        for segment in self.contours.iter().flat_map(|c| c.iter()) {
+            for p in segment.points() {
+                bounds.0 = bounds.0.min(p.x);
+                bounds.1 = bounds.1.min(p.y);
+                bounds.2 = bounds.2.max(p.x);
+                bounds.3 = bounds.3.max(p.y);
+            }
+        }
+        bounds
+    }
+
+    /// Whether `p` is inside the shape with the non-zero fill rule. Curves
+    /// are flattened so the result is only approximate very close to them.
+    pub fn contains(&self, p: Vector) -> bool{
+        let mut winding = 0;
+        for segment in self.contours.iter().flat_map(|c| c.iter()) {
+            for (a, b) in segment.flatten(16) {
+                if (a.y <= p.y) != (b.y <= p.y) {
+                    let x = a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x);
+                    if x > p.x {
+                        winding += if b.y > a.y { 1 } else { -1 };
+                    }
+                }
+            }
+        }
+        winding != 0
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use outline::{Contour as OutlineContour, ControlBox, Outline, PathCommand, Winding};
+
+    fn sorted(mut roots: Vec<f64>) -> Vec<f64>{
+        roots.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        roots
+    }
+
+    fn assert_roots(roots: Vec<f64>, expected: &[f64]){
+        let roots = sorted(roots);
+        assert_eq!(roots.len(), expected.len(), "{:?}", roots);
+        for (root, expected) in roots.iter().zip(expected) {
+            assert!((root - expected).abs() < 1e-9, "{:?} != {:?}", roots, expected);
+        }
+    }
+
+    #[test]
+    fn quadratic_roots(){
+        assert_roots(solve_quadratic(1., -3., 2.), &[1., 2.]);
+        assert_roots(solve_quadratic(1., -2., 1.), &[1.]);
+        assert_roots(solve_quadratic(1., 0., 1.), &[]);
+        // linear and constant
+        assert_roots(solve_quadratic(0., 2., -4.), &[2.]);
+        assert_roots(solve_quadratic(0., 0., 1.), &[]);
+    }
+
+    #[test]
+    fn cubic_roots(){
+        assert_roots(solve_cubic(1., -6., 11., -6.), &[1., 2., 3.]);
+        assert_roots(solve_cubic(2., -12., 22., -12.), &[1., 2., 3.]);
+        assert_roots(solve_cubic(1., 0., 0., -8.), &[2.]);
+        // falls back to the quadratic
+        assert_roots(solve_cubic(0., 1., -3., 2.), &[1., 2.]);
+    }
+
+    fn v(x: f64, y: f64) -> Vector{
+        Vector::new(x, y)
+    }
+
+    #[test]
+    fn line_distance(){
+        let line = Segment::Line([v(0., 0.), v(10., 0.)]);
+        let (distance, param) = line.signed_distance(v(5., 3.));
+        assert_eq!((distance.distance, distance.dot, param), (-3., 0., 0.5));
+        let (distance, _) = line.signed_distance(v(5., -3.));
+        assert_eq!(distance.distance, 3.);
+    }
+
+    #[test]
+    fn line_distance_past_the_end(){
+        let line = Segment::Line([v(0., 0.), v(10., 0.)]);
+        let (distance, param) = line.signed_distance(v(13., 4.));
+        assert!((distance.distance + 5.).abs() < 1e-12);
+        assert!((distance.dot - 0.6).abs() < 1e-12);
+        assert!((param - 1.3).abs() < 1e-12);
+    }
+
+    #[test]
+    fn quad_distance(){
+        // apex at (5, 5)
+        let quad = Segment::Quad([v(0., 0.), v(5., 10.), v(10., 0.)]);
+        let (distance, param) = quad.signed_distance(v(5., 7.));
+        assert!((distance.distance.abs() - 2.).abs() < 1e-9, "{:?}", distance);
+        assert!((param - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cubic_distance(){
+        // apex at (5, 7.5)
+        let cubic = Segment::Cubic([v(0., 0.), v(0., 10.), v(10., 10.), v(10., 0.)]);
+        let (distance, param) = cubic.signed_distance(v(5., 9.));
+        assert!((distance.distance.abs() - 1.5).abs() < 1e-6, "{:?}", distance);
+        assert!((param - 0.5).abs() < 1e-6);
+    }
+
+    /// Closed square from (x0, y0) to (x1, y1), counter clockwise when
+    /// x0 < x1 and y0 < y1.
+    fn square(x0: f64, y0: f64, x1: f64, y1: f64) -> Contour{
+        vec![
+            Segment::Line([v(x0, y0), v(x1, y0)]),
+            Segment::Line([v(x1, y0), v(x1, y1)]),
+            Segment::Line([v(x1, y1), v(x0, y1)]),
+            Segment::Line([v(x0, y1), v(x0, y0)]),
+        ]
+    }
+
+    /// `contour` of lines going the other way around.
+    fn reversed(mut contour: Contour) -> Contour{
+        contour.reverse();
+        for segment in &mut contour {
+            if let Segment::Line(ref mut p) = *segment {
+                p.swap(0, 1);
+            }
+        }
+        contour
+    }
+
+    #[test]
+    fn outline_round_trip(){
+        let shape = Shape{ contours: vec![
+            vec![
+                Segment::Line([v(0., 0.), v(8., 0.)]),
+                Segment::Quad([v(8., 0.), v(12., 4.), v(8., 8.)]),
+                Segment::Cubic([v(8., 8.), v(6., 10.), v(2., 10.), v(0., 8.)]),
+                Segment::Line([v(0., 8.), v(0., 0.)]),
+            ],
+            square(2., 2., 4., 4.),
+        ]};
+        let outline = Outline::from_shape(&shape);
+        assert_eq!(outline.contours[0].commands, vec![
+            PathCommand::MoveTo([0., 0.]),
+            PathCommand::LineTo([8., 0.]),
+            PathCommand::QuadTo([12., 4.], [8., 8.]),
+            PathCommand::CubicTo([6., 10.], [2., 10.], [0., 8.]),
+            PathCommand::LineTo([0., 0.]),
+            PathCommand::Close,
+        ]);
+        assert_eq!(outline.control_box, ControlBox{ x_min: 0., y_min: 0., x_max: 12., y_max: 10. });
+        assert_eq!(outline.commands().count(), 12);
+        assert_eq!(outline.to_shape().contours, shape.contours);
+    }
+
+    #[test]
+    fn outline_close_adds_closing_line(){
+        let outline = Outline{
+            contours: vec![OutlineContour{
+                commands: vec![
+                    PathCommand::MoveTo([0., 0.]),
+                    PathCommand::LineTo([4., 0.]),
+                    PathCommand::LineTo([4., 4.]),
+                    PathCommand::Close,
+                ],
+                winding: Winding::CounterClockwise,
+            }],
+            control_box: ControlBox::default(),
+        };
+        assert_eq!(outline.to_shape().contours, vec![vec![
+            Segment::Line([v(0., 0.), v(4., 0.)]),
+            Segment::Line([v(4., 0.), v(4., 4.)]),
+            Segment::Line([v(4., 4.), v(0., 0.)]),
+        ]]);
+    }
+
+    #[test]
+    fn outline_winding(){
+        let reversed = reversed(square(0., 0., 10., 10.));
+        let shape = Shape{ contours: vec![square(0., 0., 10., 10.), reversed] };
+        let outline = Outline::from_shape(&shape);
+        assert_eq!(outline.contours[0].winding, Winding::CounterClockwise);
+        assert_eq!(outline.contours[1].winding, Winding::Clockwise);
+
+        let empty = Outline::from_shape(&Shape::default());
+        assert!(empty.contours.is_empty());
+        assert_eq!(empty.control_box, ControlBox::default());
+    }
+
+    #[test]
+    fn non_zero_fill_rule(){
+        let hole = reversed(square(2., 2., 8., 8.));
+        let with_hole = Shape{ contours: vec![square(0., 0., 10., 10.), hole] };
+        assert!(with_hole.contains(v(1., 5.)));
+        assert!(!with_hole.contains(v(5., 5.)));
+        assert!(!with_hole.contains(v(11., 5.)));
+        // the outline keeps the fill
+        let round_trip = Outline::from_shape(&with_hole).to_shape();
+        assert!(round_trip.contains(v(1., 5.)));
+        assert!(!round_trip.contains(v(5., 5.)));
+
+        // overlapping contours in the same direction add up
+        let overlap = Shape{ contours: vec![square(0., 0., 10., 10.), square(2., 2., 8., 8.)] };
+        assert!(overlap.contains(v(5., 5.)));
+    }
+}
@@ -0,0 +1,137 @@
+use std::ptr;
+
+use ffi;
+use raster::Bitmap;
+
+/// Shape of the outline stroke where two segments meet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineJoin{
+    /// Rounded corners, the only join freetype-gl supports.
+    Round,
+    /// Corners cut flat.
+    Bevel,
+    /// Sharp corners, beveled instead when they'd be longer than the miter
+    /// limit, like SVG's miter joins.
+    Miter,
+    /// Sharp corners, cut flat at the miter limit when they'd be longer.
+    MiterClipped,
+}
+
+/// Shape of the outline stroke at the ends of open contours, which only
+/// some fonts have.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineCap{
+    Butt,
+    Round,
+    Square,
+}
+
+/// How glyph outlines are stroked in the outline render modes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StrokeOptions{
+    /// `LineJoin::Round` by default.
+    pub join: LineJoin,
+    /// `LineCap::Round` by default.
+    pub cap: LineCap,
+    /// Maximum distance of miter join tips from the corner of the outline
+    /// as a multiple of the outline thickness, which is the stroke radius,
+    /// not its full width. 4 by default, values under 1 are treated as 1.
+    pub miter_limit: f32,
+}
+
+impl Default for StrokeOptions{
+    fn default() -> StrokeOptions{
+        StrokeOptions{
+            join: LineJoin::Round,
+            cap: LineCap::Round,
+            miter_limit: 4.,
+        }
+    }
+}
+
+impl StrokeOptions{
+    /// Whether freetype-gl strokes glyphs the same way.
+    #[inline]
+    pub(crate) fn freetype_gl_compatible(&self) -> bool{
+        self.join == LineJoin::Round && self.cap == LineCap::Round
+    }
+
+    fn line_join(&self) -> ffi::FT_Stroker_LineJoin{
+        match self.join {
+            LineJoin::Round => ffi::FT_Stroker_LineJoin__FT_STROKER_LINEJOIN_ROUND,
+            LineJoin::Bevel => ffi::FT_Stroker_LineJoin__FT_STROKER_LINEJOIN_BEVEL,
+            LineJoin::Miter => ffi::FT_Stroker_LineJoin__FT_STROKER_LINEJOIN_MITER_FIXED,
+            LineJoin::MiterClipped => ffi::FT_Stroker_LineJoin__FT_STROKER_LINEJOIN_MITER_VARIABLE,
+        }
+    }
+
+    fn line_cap(&self) -> ffi::FT_Stroker_LineCap{
+        match self.cap {
+            LineCap::Butt => ffi::FT_Stroker_LineCap__FT_STROKER_LINECAP_BUTT,
+            LineCap::Round => ffi::FT_Stroker_LineCap__FT_STROKER_LINECAP_ROUND,
+            LineCap::Square => ffi::FT_Stroker_LineCap__FT_STROKER_LINECAP_SQUARE,
+        }
+    }
+}
+
+/// Rendered glyph image owned by freetype.
+pub(crate) struct BitmapGlyph(ffi::FT_BitmapGlyph);
+
+impl Drop for BitmapGlyph{
+    fn drop(&mut self){
+        unsafe{ ffi::FT_Done_Glyph(self.0 as ffi::FT_Glyph) }
+    }
+}
+
+impl BitmapGlyph{
+    /// `depth` is the number of bytes per pixel of the rendered data.
+    pub fn bitmap<'a>(&'a self, depth: usize) -> Bitmap<'a>{
+        unsafe{
+            Bitmap::from_ft_bitmap(&(*self.0).bitmap, (*self.0).left, (*self.0).top, depth)
+        }
+    }
+}
+
+/// Strokes the outline loaded in `slot` with the font's outline thickness,
+/// keeping the borders its outline render mode requires, and renders it with
+/// `render_mode`. Returns None on failure.
+pub(crate) unsafe fn stroke_glyph(
+    font: *mut ffi::texture_font_t,
+    slot: ffi::FT_GlyphSlot,
+    options: &StrokeOptions,
+    render_mode: ffi::FT_Render_Mode) -> Option<BitmapGlyph>
+{
+    let mut stroker = ptr::null_mut();
+    if ffi::FT_Stroker_New((*font).library, &mut stroker) != 0 {
+        return None
+    }
+    ffi::FT_Stroker_Set(
+        stroker,
+        ((*font).outline_thickness * 64.) as ffi::FT_Fixed,
+        options.line_cap(),
+        options.line_join(),
+        (options.miter_limit.max(1.) * 65536.) as ffi::FT_Fixed);
+
+    let mut glyph = ptr::null_mut();
+    let mut error = ffi::FT_Get_Glyph(slot, &mut glyph);
+    if error == 0 {
+        // Same borders as freetype-gl for each mode
+        error = match (*font).rendermode {
+            ffi::rendermode_t_RENDER_OUTLINE_EDGE => ffi::FT_Glyph_Stroke(&mut glyph, stroker, 1),
+            ffi::rendermode_t_RENDER_OUTLINE_POSITIVE => ffi::FT_Glyph_StrokeBorder(&mut glyph, stroker, 0, 1),
+            ffi::rendermode_t_RENDER_OUTLINE_NEGATIVE => ffi::FT_Glyph_StrokeBorder(&mut glyph, stroker, 1, 1),
+            _ => 0,
+        };
+    }
+    ffi::FT_Stroker_Done(stroker);
+    if error == 0 {
+        error = ffi::FT_Glyph_To_Bitmap(&mut glyph, render_mode, ptr::null(), 1);
+    }
+    if error != 0 {
+        if !glyph.is_null() {
+            ffi::FT_Done_Glyph(glyph);
+        }
+        return None
+    }
+    Some(BitmapGlyph(glyph as ffi::FT_BitmapGlyph))
+}
@@ -0,0 +1,76 @@
+use std::ffi::CStr;
+use std::os::raw::c_void;
+use std::ptr;
+use std::slice;
+
+use ffi;
+
+/// Design axis of a variable font.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VariationAxis{
+    /// OpenType tag, eg. `*b"wght"`.
+    pub tag: [u8; 4],
+    pub name: String,
+    pub minimum: f32,
+    pub default: f32,
+    pub maximum: f32,
+}
+
+/// Axes of `face`, empty for fonts without variations.
+pub(crate) unsafe fn axes(face: ffi::FT_Face) -> Vec<VariationAxis>{
+    if (*face).face_flags & ffi::FT_FACE_FLAG_MULTIPLE_MASTERS as ffi::FT_Long == 0 {
+        return vec![]
+    }
+    let mut master = ptr::null_mut();
+    if ffi::FT_Get_MM_Var(face, &mut master) != 0 || master.is_null() {
+        return vec![]
+    }
+    let axes = slice::from_raw_parts((*master).axis, (*master).num_axis as usize).iter().map(|axis| {
+        VariationAxis{
+            tag: [(axis.tag >> 24) as u8, (axis.tag >> 16) as u8, (axis.tag >> 8) as u8, axis.tag as u8],
+            name: if axis.name.is_null() {
+                String::new()
+            }else{
+                CStr::from_ptr(axis.name).to_string_lossy().into_owned()
+            },
+            minimum: axis.minimum as f32 / 65536.,
+            default: axis.def as f32 / 65536.,
+            maximum: axis.maximum as f32 / 65536.,
+        }
+    }).collect();
+    // FT_Done_MM_Var only exists since FreeType 2.9, before that the
+    // description is freed with the face's memory manager
+    let memory = (*face).memory;
+    if let Some(free) = (*memory).free {
+        free(memory, master as *mut c_void);
+    }
+    axes
+}
+
+/// Current design coordinates of the `num_axes` axes of `face`, in 16.16.
+pub(crate) unsafe fn coordinates(face: ffi::FT_Face, num_axes: usize) -> Vec<ffi::FT_Fixed>{
+    let mut coordinates = vec![0; num_axes];
+    if num_axes > 0 {
+        ffi::FT_Get_Var_Design_Coordinates(face, num_axes as ffi::FT_UInt, coordinates.as_mut_ptr());
+    }
+    coordinates
+}
+
+/// Sets `value` on the axis `tag` in `coordinates`, clamped to the axis
+/// range. Returns false if there's no such axis.
+pub(crate) fn set(axes: &[VariationAxis], coordinates: &mut [ffi::FT_Fixed], tag: &[u8; 4], value: f32) -> bool{
+    match axes.iter().position(|axis| axis.tag == *tag) {
+        Some(i) => {
+            let axis = &axes[i];
+            coordinates[i] = (value.max(axis.minimum).min(axis.maximum) * 65536.).round() as ffi::FT_Fixed;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Selects the design `coordinates` in `face`.
+pub(crate) unsafe fn apply(face: ffi::FT_Face, coordinates: &mut [ffi::FT_Fixed]) -> bool{
+    coordinates.is_empty()
+        || ffi::FT_Set_Var_Design_Coordinates(face, coordinates.len() as ffi::FT_UInt, coordinates.as_mut_ptr()) == 0
+}